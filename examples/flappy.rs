@@ -62,7 +62,7 @@ impl MainState {
 
     fn new(ctx: &mut ggez::Context) -> Self {
         let args = helper::cli::get_arguments();
-        let params = helper::read_parameters_file("./params/flappy.toml");
+        let params = helper::read_parameters_file("./params/flappy.toml").unwrap();
 
         let mut innov_record = InnovationRecord::new(params.input_number, params.output_number);
         let pool = Pool::<Feedforward>::new(params.clone(), args.verbosity, &mut innov_record);
@@ -139,7 +139,7 @@ impl event::EventHandler for MainState {
                         (bird.rect().bottom() - current_pipe.lower_rect().top()).into(),
                     ],
                 )
-                .unwrap();
+                .map_err(helper::game_error)?;
 
             if output[0] > 0.5 {
                 bird.jump();
@@ -159,13 +159,16 @@ impl event::EventHandler for MainState {
             let mut best_network = self
                 .pool
                 .evaluate(|i, network| network.evaluate(fitness_list[i]))
+                .map_err(helper::game_error)?
                 .clone();
             let best_fitness = best_network.fitness().unwrap();
 
             self.layout
                 .update(best_network.graph_mut(), best_fitness, generation);
 
-            self.pool.evolve(&mut self.innov_record);
+            self.pool
+                .evolve(&mut self.innov_record)
+                .map_err(helper::game_error)?;
             self.reset_birds();
             self.reset_pipes();
 
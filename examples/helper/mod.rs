@@ -1,7 +1,10 @@
 #![allow(dead_code)]
 
-use neat::parameters::Parameters;
-use std::fs;
+use ggez::GameError;
+use neat::{
+    error::NeatError,
+    parameters::{Parameters, ParametersLoader},
+};
 
 pub mod cli;
 pub mod flappy;
@@ -11,15 +14,14 @@ pub mod opencolor;
 pub mod plot;
 pub mod text;
 
-pub fn read_parameters_file(path: &str) -> Parameters {
-    let params_str;
-    if let Ok(str) = fs::read_to_string(path) {
-        params_str = str;
-    } else {
-        panic!("Couldn't read params file path: {}", path);
-    }
+// Fields can be overridden by environment variables, e.g. NEAT_MUTATION__ADD_NODE=0.1
+pub fn read_parameters_file(path: &str) -> Result<Parameters, NeatError> {
+    ParametersLoader::new().file(path)?.env("NEAT")?.load()
+}
 
-    toml::from_str(&params_str).unwrap()
+// ggez has no variant for errors from other libraries
+pub fn game_error(e: NeatError) -> GameError {
+    GameError::ConfigError(e.to_string())
 }
 
 #[allow(dead_code)]
@@ -29,7 +29,7 @@ struct MainState {
 impl MainState {
     fn new(ctx: &mut ggez::Context) -> Self {
         let args = helper::cli::get_arguments();
-        let params = helper::read_parameters_file("./params/sin.toml");
+        let params = helper::read_parameters_file("./params/sin.toml").unwrap();
 
         let mut innov_record = InnovationRecord::new(1, 1);
         let pool = Pool::<Feedforward>::new(params.clone(), args.verbosity, &mut innov_record);
@@ -76,14 +76,15 @@ impl event::EventHandler for MainState {
                 .pool
                 .evaluate(|_, network| {
                     let n = 50;
-                    let mut error_sum = 0.0;
+                    let xs: Vec<[f64; 1]> = (-n..=n).map(|i| [1.0 * i as f64 / n as f64]).collect();
+                    let inputs: Vec<&[f64]> = xs.iter().map(|x| &x[..]).collect();
 
-                    for i in -n..=n {
-                        let x = 1.0 * i as f64 / n as f64;
+                    let outputs = network.activate_batch(&inputs).unwrap();
+                    let mut error_sum = 0.0;
 
-                        let output = network.activate(&[x]).unwrap()[0];
-                        let expected = (2.0 * x * std::f64::consts::PI).sin() * 0.5;
-                        let err = output - expected;
+                    for (x, output) in xs.iter().zip(&outputs) {
+                        let expected = (2.0 * x[0] * std::f64::consts::PI).sin() * 0.5;
+                        let err = output[0] - expected;
 
                         error_sum += err * err;
                     }
@@ -91,6 +92,7 @@ impl event::EventHandler for MainState {
                     let error_mean = error_sum / (n * 2 + 1) as f64;
                     network.evaluate(4.0 - error_mean);
                 })
+                .map_err(helper::game_error)?
                 .clone();
             let best_fitness = best_network.fitness().unwrap();
 
@@ -100,7 +102,9 @@ impl event::EventHandler for MainState {
 
             self.layout
                 .update(best_network.graph_mut(), best_fitness, generation);
-            self.pool.evolve(&mut self.innov_record);
+            self.pool
+                .evolve(&mut self.innov_record)
+                .map_err(helper::game_error)?;
             self.timer = Duration::new(0, 0);
         }
 
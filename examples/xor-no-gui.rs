@@ -1,15 +1,16 @@
 #![recursion_limit = "512"]
 mod helper;
 
-use neat::network::Network;
+use neat::termination::StopCondition;
+use neat::{error::NeatError, network::Network};
 use neat::{innovation_record::InnovationRecord, network::feedforward::Feedforward, pool::Pool};
 
-pub fn main() {
+pub fn main() -> Result<(), NeatError> {
     for _ in 0..500 {
         println!("<Case Start>");
 
         let args = helper::cli::get_arguments();
-        let params = helper::read_parameters_file("./params/xor.toml");
+        let params = helper::read_parameters_file("./params/xor.toml")?;
 
         let mut innov_record = InnovationRecord::new(2, 1);
         let mut pool = Pool::<Feedforward>::new(params, args.verbosity, &mut innov_record);
@@ -20,20 +21,23 @@ pub fn main() {
             (vec![1.0, 0.0], 1.0),
             (vec![1.0, 1.0], 0.0),
         ];
+        let inputs: Vec<&[f64]> = data.iter().map(|(inputs, _)| &inputs[..]).collect();
 
-        for _ in 0..200 {
-            pool.evaluate(|_, network| {
+        pool.run(
+            &mut innov_record,
+            |_, network| {
+                let outputs = network.activate_batch(&inputs).unwrap();
                 let mut err = 0.0;
 
-                for (inputs, expected) in &data {
-                    let output = network.activate(inputs).unwrap()[0];
-                    err += (output - expected) * (output - expected);
+                for (output, (_, expected)) in outputs.iter().zip(&data) {
+                    err += (output[0] - expected) * (output[0] - expected);
                 }
 
                 network.evaluate(4.0 - err);
-            });
-
-            pool.evolve(&mut innov_record);
-        }
+            },
+            StopCondition::new().max_generations(200),
+        )?;
     }
+
+    Ok(())
 }
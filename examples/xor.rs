@@ -29,7 +29,7 @@ struct MainState {
 impl MainState {
     fn new(ctx: &mut ggez::Context) -> Self {
         let args = helper::cli::get_arguments();
-        let params = helper::read_parameters_file("./params/xor.toml");
+        let params = helper::read_parameters_file("./params/xor.toml").unwrap();
 
         let mut innov_record = InnovationRecord::new(2, 1);
         let pool = Pool::<Feedforward>::new(params.clone(), args.verbosity, &mut innov_record);
@@ -83,20 +83,22 @@ impl event::EventHandler for MainState {
                 (vec![1.0, 0.0], 1.0),
                 (vec![1.0, 1.0], 0.0),
             ];
+            let inputs: Vec<&[f64]> = data.iter().map(|(inputs, _)| &inputs[..]).collect();
 
             let generation = self.pool.generation();
             let mut best_network = self
                 .pool
                 .evaluate(|_, network| {
+                    let outputs = network.activate_batch(&inputs).unwrap();
                     let mut err = 0.0;
 
-                    for (inputs, expected) in &data {
-                        let output = network.activate(inputs).unwrap()[0];
-                        err += (output - expected) * (output - expected);
+                    for (output, (_, expected)) in outputs.iter().zip(&data) {
+                        err += (output[0] - expected) * (output[0] - expected);
                     }
 
                     network.evaluate(4.0 - err);
                 })
+                .map_err(helper::game_error)?
                 .clone();
             let best_fitness = best_network.fitness().unwrap();
 
@@ -109,7 +111,9 @@ impl event::EventHandler for MainState {
 
             self.layout
                 .update(best_network.graph_mut(), best_fitness, generation);
-            self.pool.evolve(&mut self.innov_record);
+            self.pool
+                .evolve(&mut self.innov_record)
+                .map_err(helper::game_error)?;
             self.timer = Duration::new(0, 0);
         }
 
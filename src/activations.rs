@@ -1,11 +1,157 @@
-use serde::Deserialize;
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+    sync::RwLock,
+};
 
-#[derive(Deserialize, Clone, Copy, Debug)]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ActivationKind {
     Sigmoid,
     Tanh,
     Linear,
     Relu,
+    Gaussian,
+    Sine,
+    Cosine,
+    Abs,
+    Step,
+    Softplus,
+    Elu,
+    LeakyRelu,
+    Square,
+    Cube,
+    Inverse,
+    Log,
+    Exp,
+    Hat,
+    Clamped,
+
+    // Activation registered with register_activation
+    Custom(CustomActivation),
+}
+
+const BUILTIN_ACTIVATIONS: [(&str, ActivationKind); 19] = [
+    ("Sigmoid", ActivationKind::Sigmoid),
+    ("Tanh", ActivationKind::Tanh),
+    ("Linear", ActivationKind::Linear),
+    ("Relu", ActivationKind::Relu),
+    ("Gaussian", ActivationKind::Gaussian),
+    ("Sine", ActivationKind::Sine),
+    ("Cosine", ActivationKind::Cosine),
+    ("Abs", ActivationKind::Abs),
+    ("Step", ActivationKind::Step),
+    ("Softplus", ActivationKind::Softplus),
+    ("Elu", ActivationKind::Elu),
+    ("LeakyRelu", ActivationKind::LeakyRelu),
+    ("Square", ActivationKind::Square),
+    ("Cube", ActivationKind::Cube),
+    ("Inverse", ActivationKind::Inverse),
+    ("Log", ActivationKind::Log),
+    ("Exp", ActivationKind::Exp),
+    ("Hat", ActivationKind::Hat),
+    ("Clamped", ActivationKind::Clamped),
+];
+
+// Activation created by register_activation, which carries its function so that activating
+// doesn't need to look it up. Activations are equal if their names are.
+#[derive(Clone, Copy)]
+pub struct CustomActivation {
+    name: &'static str,
+    func: fn(f64) -> f64,
+}
+
+impl CustomActivation {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl PartialEq for CustomActivation {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl fmt::Debug for CustomActivation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("CustomActivation").field(&self.name).finish()
+    }
+}
+
+// Looked up by name only when parsing, e.g. loading parameters or genomes
+static CUSTOM_ACTIVATIONS: RwLock<Vec<CustomActivation>> = RwLock::new(Vec::new());
+
+// Registers a user-defined activation, which can then be referred by its name in parameters
+// and saved genomes. Registering an existing name replaces its function for activations
+// parsed afterwards. Returns None if the name is already taken by a built-in activation.
+pub fn register_activation(name: &str, func: fn(f64) -> f64) -> Option<ActivationKind> {
+    if BUILTIN_ACTIVATIONS.iter().any(|&(n, _)| n == name) {
+        return None;
+    }
+
+    let mut customs = CUSTOM_ACTIVATIONS.write().unwrap();
+    let custom = match customs.iter_mut().find(|custom| custom.name == name) {
+        Some(custom) => {
+            custom.func = func;
+            *custom
+        }
+        None => {
+            // Names live as long as the registry, which is never cleared
+            let custom = CustomActivation {
+                name: Box::leak(name.to_owned().into_boxed_str()),
+                func,
+            };
+            customs.push(custom);
+            custom
+        }
+    };
+
+    Some(ActivationKind::Custom(custom))
+}
+
+impl Display for ActivationKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let ActivationKind::Custom(custom) = self {
+            return write!(f, "{}", custom.name);
+        }
+
+        let (name, _) = BUILTIN_ACTIVATIONS
+            .iter()
+            .find(|&&(_, kind)| kind == *self)
+            .unwrap();
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for ActivationKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(&(_, kind)) = BUILTIN_ACTIVATIONS.iter().find(|&&(name, _)| name == s) {
+            return Ok(kind);
+        }
+
+        let customs = CUSTOM_ACTIVATIONS.read().unwrap();
+        match customs.iter().find(|custom| custom.name == s) {
+            Some(&custom) => Ok(ActivationKind::Custom(custom)),
+            None => Err(format!("unknown activation function '{}'", s)),
+        }
+    }
+}
+
+impl Serialize for ActivationKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ActivationKind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(de::Error::custom)
+    }
 }
 
 pub fn activate(kind: ActivationKind, v: f64) -> f64 {
@@ -14,6 +160,22 @@ pub fn activate(kind: ActivationKind, v: f64) -> f64 {
         ActivationKind::Tanh => tanh(v),
         ActivationKind::Linear => linear(v),
         ActivationKind::Relu => relu(v),
+        ActivationKind::Gaussian => gaussian(v),
+        ActivationKind::Sine => sine(v),
+        ActivationKind::Cosine => cosine(v),
+        ActivationKind::Abs => abs(v),
+        ActivationKind::Step => step(v),
+        ActivationKind::Softplus => softplus(v),
+        ActivationKind::Elu => elu(v),
+        ActivationKind::LeakyRelu => leaky_relu(v),
+        ActivationKind::Square => square(v),
+        ActivationKind::Cube => cube(v),
+        ActivationKind::Inverse => inverse(v),
+        ActivationKind::Log => log(v),
+        ActivationKind::Exp => exp(v),
+        ActivationKind::Hat => hat(v),
+        ActivationKind::Clamped => clamped(v),
+        ActivationKind::Custom(custom) => (custom.func)(v),
     }
 }
 
@@ -32,3 +194,161 @@ pub fn linear(v: f64) -> f64 {
 pub fn relu(v: f64) -> f64 {
     v.max(0.0)
 }
+
+pub fn gaussian(v: f64) -> f64 {
+    (-v * v).exp()
+}
+
+pub fn sine(v: f64) -> f64 {
+    v.sin()
+}
+
+pub fn cosine(v: f64) -> f64 {
+    v.cos()
+}
+
+pub fn abs(v: f64) -> f64 {
+    v.abs()
+}
+
+pub fn step(v: f64) -> f64 {
+    if v > 0.0 {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+pub fn softplus(v: f64) -> f64 {
+    // Rearranged to avoid overflow of exp for large inputs
+    v.max(0.0) + (-v.abs()).exp().ln_1p()
+}
+
+pub fn elu(v: f64) -> f64 {
+    if v > 0.0 {
+        v
+    } else {
+        v.exp_m1()
+    }
+}
+
+pub fn leaky_relu(v: f64) -> f64 {
+    if v > 0.0 {
+        v
+    } else {
+        0.01 * v
+    }
+}
+
+pub fn square(v: f64) -> f64 {
+    v * v
+}
+
+pub fn cube(v: f64) -> f64 {
+    v * v * v
+}
+
+pub fn inverse(v: f64) -> f64 {
+    if v == 0.0 {
+        0.0
+    } else {
+        1.0 / v
+    }
+}
+
+pub fn log(v: f64) -> f64 {
+    v.max(1e-7).ln()
+}
+
+pub fn exp(v: f64) -> f64 {
+    v.clamp(-60.0, 60.0).exp()
+}
+
+pub fn hat(v: f64) -> f64 {
+    (1.0 - v.abs()).max(0.0)
+}
+
+pub fn clamped(v: f64) -> f64 {
+    v.clamp(-1.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_cmp::approx_eq;
+
+    #[test]
+    fn activations_should_be_calculated_correctly() {
+        let cases = [
+            (ActivationKind::Gaussian, 0.0, 1.0),
+            (ActivationKind::Sine, std::f64::consts::FRAC_PI_2, 1.0),
+            (ActivationKind::Cosine, 0.0, 1.0),
+            (ActivationKind::Abs, -2.0, 2.0),
+            (ActivationKind::Step, 0.5, 1.0),
+            (ActivationKind::Step, -0.5, 0.0),
+            (ActivationKind::Softplus, 0.0, std::f64::consts::LN_2),
+            (ActivationKind::Softplus, 1000.0, 1000.0),
+            (ActivationKind::Elu, -1.0, (-1.0f64).exp() - 1.0),
+            (ActivationKind::LeakyRelu, -2.0, -0.02),
+            (ActivationKind::Square, -3.0, 9.0),
+            (ActivationKind::Cube, -2.0, -8.0),
+            (ActivationKind::Inverse, 4.0, 0.25),
+            (ActivationKind::Inverse, 0.0, 0.0),
+            (ActivationKind::Log, 1.0, 0.0),
+            (ActivationKind::Exp, 0.0, 1.0),
+            (ActivationKind::Hat, 0.25, 0.75),
+            (ActivationKind::Hat, 2.0, 0.0),
+            (ActivationKind::Clamped, 3.0, 1.0),
+        ];
+
+        for &(kind, input, expected) in &cases {
+            assert!(
+                approx_eq!(f64, activate(kind, input), expected, epsilon = 1e-12),
+                "{}({}) should be {}",
+                kind,
+                input,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn activations_should_be_deserialized_by_name() {
+        #[derive(Deserialize)]
+        struct Options {
+            options: Vec<ActivationKind>,
+        }
+
+        let options: Options =
+            toml::from_str("options = ['Gaussian', 'LeakyRelu', 'Hat']").unwrap();
+        assert_eq!(
+            options.options,
+            vec![
+                ActivationKind::Gaussian,
+                ActivationKind::LeakyRelu,
+                ActivationKind::Hat
+            ]
+        );
+        assert!(toml::from_str::<Options>("options = ['Unknown']").is_err());
+    }
+
+    #[test]
+    fn custom_activation_should_be_registered_by_name() {
+        let kind = register_activation("DoubleForTest", |v| v * 2.0).unwrap();
+
+        assert_eq!(activate(kind, 1.5), 3.0);
+        assert_eq!(kind.to_string(), "DoubleForTest");
+        assert_eq!("DoubleForTest".parse::<ActivationKind>(), Ok(kind));
+        assert_eq!(
+            register_activation("DoubleForTest", |v| v * 2.0),
+            Some(kind)
+        );
+        assert_eq!(register_activation("Sigmoid", |v| v), None);
+
+        // Already created activations keep their function
+        let tripled = register_activation("DoubleForTest", |v| v * 3.0).unwrap();
+        assert_eq!(activate(kind, 1.0), 2.0);
+        assert_eq!(activate(tripled, 1.0), 3.0);
+        assert_eq!(serde_json::to_string(&kind).unwrap(), "\"DoubleForTest\"");
+    }
+}
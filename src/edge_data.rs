@@ -1,4 +1,6 @@
-#[derive(Debug, PartialEq, Clone, Default)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
 pub struct EdgeData {
     weight: f64,
     disabled: bool,
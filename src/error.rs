@@ -0,0 +1,66 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+use crate::serialization::SerializationError;
+
+#[derive(Debug)]
+pub enum NeatError {
+    InputSizeMismatch { expected: usize, actual: usize },
+    CyclicGraph,
+    ExtinctPopulation,
+
+    // Description of every invalid field
+    InvalidParameters(Vec<String>),
+    IncompatibleCrossover(String),
+    InvalidDataset(String),
+    InvalidBehavior(String),
+    InvalidObjectives(String),
+    InvalidFitness(String),
+    InvalidNetwork(String),
+
+    Serialization(SerializationError),
+}
+
+impl Display for NeatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NeatError::InputSizeMismatch { expected, actual } => {
+                write!(f, "expected {} inputs, got {}", expected, actual)
+            }
+            NeatError::CyclicGraph => write!(f, "network graph has a cycle"),
+            NeatError::ExtinctPopulation => write!(
+                f,
+                "no species survived; maybe compatibility threshold is too small?"
+            ),
+            NeatError::InvalidParameters(errors) => {
+                write!(f, "invalid parameters: {}", errors.join(", "))
+            }
+            NeatError::IncompatibleCrossover(reason) => {
+                write!(f, "couldn't cross over genomes: {}", reason)
+            }
+            NeatError::InvalidDataset(reason) => write!(f, "invalid dataset: {}", reason),
+            NeatError::InvalidBehavior(reason) => write!(f, "invalid behavior: {}", reason),
+            NeatError::InvalidObjectives(reason) => write!(f, "invalid objectives: {}", reason),
+            NeatError::InvalidFitness(reason) => write!(f, "invalid fitness: {}", reason),
+            NeatError::InvalidNetwork(reason) => write!(f, "invalid network: {}", reason),
+            NeatError::Serialization(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for NeatError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            NeatError::Serialization(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<SerializationError> for NeatError {
+    fn from(e: SerializationError) -> Self {
+        NeatError::Serialization(e)
+    }
+}
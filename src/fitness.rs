@@ -0,0 +1,380 @@
+use std::{convert::TryFrom, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{error::NeatError, network::Network, serialization::SerializationError};
+
+// Outputs are clamped into (EPSILON, 1 - EPSILON) so that cross-entropy stays finite
+const EPSILON: f64 = 1e-7;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Metric {
+    MeanSquaredError,
+    MeanAbsoluteError,
+
+    // Binary cross-entropy of every output, which is expected to be a probability
+    CrossEntropy,
+
+    // Ratio of correctly classified samples. A single output is classified by thresholding
+    // at 0.5, and multiple outputs by the index of the largest value.
+    Accuracy,
+}
+
+fn argmax(values: &[f64]) -> usize {
+    let mut max_index = 0;
+    for (i, &value) in values.iter().enumerate() {
+        if value > values[max_index] {
+            max_index = i;
+        }
+    }
+
+    max_index
+}
+
+impl Metric {
+    // Whether smaller values are better
+    pub fn is_loss(self) -> bool {
+        self != Metric::Accuracy
+    }
+
+    // Empty targets would make the mean undefined, so they are rejected
+    pub fn measure(self, outputs: &[Vec<f64>], targets: &[Vec<f64>]) -> Result<f64, NeatError> {
+        if outputs.len() != targets.len() {
+            return Err(NeatError::InvalidDataset(format!(
+                "{} outputs but {} targets",
+                outputs.len(),
+                targets.len()
+            )));
+        }
+
+        let pairs = || {
+            outputs
+                .iter()
+                .zip(targets)
+                .flat_map(|(output, target)| output.iter().zip(target))
+        };
+        let value_count = targets.iter().map(|target| target.len()).sum::<usize>() as f64;
+        if value_count == 0.0 {
+            return Err(NeatError::InvalidDataset(
+                "no targets to measure".to_owned(),
+            ));
+        }
+
+        let value = match self {
+            Metric::MeanSquaredError => {
+                pairs().map(|(o, t)| (o - t).powi(2)).sum::<f64>() / value_count
+            }
+            Metric::MeanAbsoluteError => {
+                pairs().map(|(o, t)| (o - t).abs()).sum::<f64>() / value_count
+            }
+            Metric::CrossEntropy => {
+                pairs()
+                    .map(|(o, t)| {
+                        let o = o.clamp(EPSILON, 1.0 - EPSILON);
+                        -(t * o.ln() + (1.0 - t) * (1.0 - o).ln())
+                    })
+                    .sum::<f64>()
+                    / value_count
+            }
+            Metric::Accuracy => {
+                let correct = outputs
+                    .iter()
+                    .zip(targets)
+                    .filter(|(output, target)| {
+                        if target.len() == 1 {
+                            output
+                                .first()
+                                .is_some_and(|&output| (output >= 0.5) == (target[0] >= 0.5))
+                        } else {
+                            argmax(output) == argmax(target)
+                        }
+                    })
+                    .count();
+                correct as f64 / targets.len() as f64
+            }
+        };
+
+        Ok(value)
+    }
+
+    // Transforms a measured value into a non-negative score to maximize, as fitness is.
+    // Losses are mapped into (0, 1] by 1 / (1 + loss), and accuracy is used as is.
+    pub fn to_score(self, value: f64) -> f64 {
+        if self.is_loss() {
+            1.0 / (1.0 + value)
+        } else {
+            value
+        }
+    }
+}
+
+// Samples of inputs and target outputs for supervised tasks
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(try_from = "DatasetData")]
+pub struct Dataset {
+    inputs: Vec<Vec<f64>>,
+    targets: Vec<Vec<f64>>,
+}
+
+// Deserialized form of a dataset, which is checked by Dataset::new
+#[derive(Deserialize)]
+struct DatasetData {
+    inputs: Vec<Vec<f64>>,
+    targets: Vec<Vec<f64>>,
+}
+
+impl TryFrom<DatasetData> for Dataset {
+    type Error = NeatError;
+
+    fn try_from(data: DatasetData) -> Result<Self, Self::Error> {
+        if data.inputs.len() != data.targets.len() {
+            return Err(NeatError::InvalidDataset(format!(
+                "{} inputs but {} targets",
+                data.inputs.len(),
+                data.targets.len()
+            )));
+        }
+
+        Self::new(data.inputs.into_iter().zip(data.targets).collect())
+    }
+}
+
+impl Dataset {
+    pub fn new(samples: Vec<(Vec<f64>, Vec<f64>)>) -> Result<Self, NeatError> {
+        let (inputs, targets): (Vec<_>, Vec<_>) = samples.into_iter().unzip();
+
+        let (input_number, target_number) = match (inputs.first(), targets.first()) {
+            (Some(input), Some(target)) => (input.len(), target.len()),
+            _ => return Err(NeatError::InvalidDataset("dataset is empty".to_owned())),
+        };
+        if input_number == 0 || target_number == 0 {
+            return Err(NeatError::InvalidDataset(
+                "samples need at least one input and one target".to_owned(),
+            ));
+        }
+        for (i, (input, target)) in inputs.iter().zip(&targets).enumerate() {
+            if input.len() != input_number || target.len() != target_number {
+                return Err(NeatError::InvalidDataset(format!(
+                    "sample {} has {} inputs and {} targets, expected {} and {}",
+                    i,
+                    input.len(),
+                    target.len(),
+                    input_number,
+                    target_number
+                )));
+            }
+        }
+
+        Ok(Self { inputs, targets })
+    }
+
+    // Parses comma-separated rows whose last target_number columns are targets. The first
+    // row is skipped as a header if it isn't numeric, and blank lines are ignored.
+    pub fn from_csv_str(s: &str, target_number: usize) -> Result<Self, NeatError> {
+        let mut samples = Vec::new();
+
+        for (i, line) in s.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let row: Result<Vec<f64>, _> = line.split(',').map(|v| v.trim().parse()).collect();
+            let row = match row {
+                Ok(row) => row,
+                Err(_) if i == 0 => continue,
+                Err(e) => {
+                    return Err(NeatError::InvalidDataset(format!("line {}: {}", i + 1, e)));
+                }
+            };
+            if row.len() <= target_number {
+                return Err(NeatError::InvalidDataset(format!(
+                    "line {}: expected more than {} columns, got {}",
+                    i + 1,
+                    target_number,
+                    row.len()
+                )));
+            }
+
+            let (input, target) = row.split_at(row.len() - target_number);
+            samples.push((input.to_vec(), target.to_vec()));
+        }
+
+        Self::new(samples)
+    }
+
+    pub fn from_csv_file(path: impl AsRef<Path>, target_number: usize) -> Result<Self, NeatError> {
+        Self::from_csv_str(
+            &std::fs::read_to_string(path).map_err(SerializationError::from)?,
+            target_number,
+        )
+    }
+
+    pub fn len(&self) -> usize {
+        self.inputs.len()
+    }
+
+    // Always false, since empty datasets can't be constructed
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+
+    pub fn input_number(&self) -> usize {
+        self.inputs[0].len()
+    }
+
+    pub fn target_number(&self) -> usize {
+        self.targets[0].len()
+    }
+
+    pub fn inputs(&self) -> &[Vec<f64>] {
+        &self.inputs
+    }
+
+    pub fn targets(&self) -> &[Vec<f64>] {
+        &self.targets
+    }
+
+    // Activates the network on every sample and measures its outputs
+    pub fn measure<T: Network>(&self, network: &mut T, metric: Metric) -> Result<f64, NeatError> {
+        if network.graph().output_number() != self.target_number() {
+            return Err(NeatError::InvalidDataset(format!(
+                "dataset has {} targets, but network has {} outputs",
+                self.target_number(),
+                network.graph().output_number()
+            )));
+        }
+
+        let inputs: Vec<&[f64]> = self.inputs.iter().map(|input| &input[..]).collect();
+        let outputs = network.activate_batch(&inputs)?;
+
+        metric.measure(&outputs, &self.targets)
+    }
+
+    // Sets the score of the metric as fitness of the network and returns it
+    //
+    //     pool.evaluate(|_, network| {
+    //         dataset.evaluate(network, Metric::MeanSquaredError).unwrap();
+    //     })?;
+    pub fn evaluate<T: Network>(&self, network: &mut T, metric: Metric) -> Result<f64, NeatError> {
+        let score = metric.to_score(self.measure(network, metric)?);
+        network.evaluate(score);
+
+        Ok(score)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        activations::ActivationKind, innovation_record::InnovationRecord,
+        network::feedforward::Feedforward,
+    };
+    use float_cmp::approx_eq;
+
+    #[test]
+    fn metrics_should_measure_outputs() {
+        let outputs = vec![vec![0.8], vec![0.4]];
+        let targets = vec![vec![1.0], vec![1.0]];
+
+        let mse = Metric::MeanSquaredError
+            .measure(&outputs, &targets)
+            .unwrap();
+        assert!(approx_eq!(f64, mse, (0.04 + 0.36) / 2.0, ulps = 2));
+        let mae = Metric::MeanAbsoluteError
+            .measure(&outputs, &targets)
+            .unwrap();
+        assert!(approx_eq!(f64, mae, (0.2 + 0.6) / 2.0, ulps = 2));
+        let cross_entropy = Metric::CrossEntropy.measure(&outputs, &targets).unwrap();
+        assert!(approx_eq!(
+            f64,
+            cross_entropy,
+            -(0.8f64.ln() + 0.4f64.ln()) / 2.0,
+            ulps = 2
+        ));
+        assert_eq!(Metric::Accuracy.measure(&outputs, &targets).unwrap(), 0.5);
+
+        let one_hot = vec![vec![0.0, 1.0], vec![1.0, 0.0]];
+        assert_eq!(Metric::Accuracy.measure(&one_hot, &one_hot).unwrap(), 1.0);
+        for metric in &[Metric::MeanSquaredError, Metric::Accuracy] {
+            assert!(matches!(
+                metric.measure(&[], &[]),
+                Err(NeatError::InvalidDataset(_))
+            ));
+            assert!(matches!(
+                metric.measure(&outputs, &targets[..1]),
+                Err(NeatError::InvalidDataset(_))
+            ));
+        }
+        assert_eq!(Metric::MeanSquaredError.to_score(0.0), 1.0);
+        assert_eq!(Metric::MeanSquaredError.to_score(3.0), 0.25);
+    }
+
+    #[test]
+    fn dataset_should_be_loaded_from_csv() {
+        let csv = "x1,x2,y\n0,0,0\n0,1,1\n\n1,0,1\n1,1,0\n";
+        let dataset = Dataset::from_csv_str(csv, 1).unwrap();
+
+        assert_eq!(dataset.len(), 4);
+        assert_eq!(dataset.input_number(), 2);
+        assert_eq!(dataset.inputs()[1], vec![0.0, 1.0]);
+        assert_eq!(dataset.targets()[1], vec![1.0]);
+
+        assert!(matches!(
+            Dataset::from_csv_str("0,0,0\n0,a,1\n", 1),
+            Err(NeatError::InvalidDataset(_))
+        ));
+        assert!(matches!(
+            Dataset::from_csv_str("0,0,0\n0,1\n", 1),
+            Err(NeatError::InvalidDataset(_))
+        ));
+        assert!(matches!(
+            Dataset::from_csv_str("x,y\n", 1),
+            Err(NeatError::InvalidDataset(_))
+        ));
+    }
+
+    #[test]
+    fn invalid_dataset_should_not_be_deserialized() {
+        let dataset = Dataset::new(vec![(vec![1.0], vec![0.0])]).unwrap();
+        let json = serde_json::to_string(&dataset).unwrap();
+        assert_eq!(serde_json::from_str::<Dataset>(&json).unwrap(), dataset);
+
+        for json in &[
+            r#"{ "inputs": [], "targets": [] }"#,
+            r#"{ "inputs": [[1.0], [1.0, 2.0]], "targets": [[0.0], [1.0]] }"#,
+            r#"{ "inputs": [[1.0]], "targets": [] }"#,
+        ] {
+            assert!(serde_json::from_str::<Dataset>(json).is_err());
+        }
+        assert_eq!(
+            Metric::Accuracy.measure(&[vec![]], &[vec![1.0]]).unwrap(),
+            0.0
+        );
+    }
+
+    #[test]
+    fn dataset_should_evaluate_network() {
+        let dataset = Dataset::new(vec![
+            (vec![1.0, 2.0], vec![3.0]),
+            (vec![0.5, 0.5], vec![0.0]),
+        ])
+        .unwrap();
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let mut network = Feedforward::new(2, 1, ActivationKind::Linear, &mut innov_record);
+
+        // Network sums inputs, which is 1 off for the second sample
+        let score = dataset
+            .evaluate(&mut network, Metric::MeanSquaredError)
+            .unwrap();
+        assert_eq!(score, 1.0 / (1.0 + 0.5));
+        assert_eq!(network.fitness(), Some(score));
+
+        let mut innov_record = InnovationRecord::new(2, 2);
+        let mut network = Feedforward::new(2, 2, ActivationKind::Linear, &mut innov_record);
+        assert!(matches!(
+            dataset.measure(&mut network, Metric::MeanSquaredError),
+            Err(NeatError::InvalidDataset(_))
+        ));
+    }
+}
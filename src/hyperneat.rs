@@ -0,0 +1,390 @@
+use std::convert::TryFrom;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    activations::{activate, ActivationKind},
+    error::NeatError,
+    network::Network,
+};
+
+// Geometric layout of a HyperNEAT phenotype network. Each node is placed on a 2D or 3D
+// coordinate, and every node is connected to every node of the next layer.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Substrate {
+    // Node coordinates of each layer, from the input layer to the output layer
+    pub layers: Vec<Vec<Vec<f64>>>,
+
+    // Connections whose CPPN output magnitude is below the threshold are not expressed
+    pub weight_threshold: f64,
+    pub max_weight: f64,
+
+    pub hidden_activation: ActivationKind,
+    pub output_activation: ActivationKind,
+}
+
+impl Substrate {
+    pub fn new(
+        layers: Vec<Vec<Vec<f64>>>,
+        weight_threshold: f64,
+        max_weight: f64,
+        hidden_activation: ActivationKind,
+        output_activation: ActivationKind,
+    ) -> Result<Self, NeatError> {
+        let substrate = Self {
+            layers,
+            weight_threshold,
+            max_weight,
+            hidden_activation,
+            output_activation,
+        };
+        substrate.validate()?;
+
+        Ok(substrate)
+    }
+
+    // Reports every invalid field at once, as Parameters::validate does
+    pub fn validate(&self) -> Result<(), NeatError> {
+        let mut errors = Vec::new();
+
+        if self.layers.len() < 2 || self.layers.iter().any(|layer| layer.is_empty()) {
+            errors.push("substrate needs two or more non-empty layers".to_owned());
+        } else if self.dimension().is_none() {
+            errors.push("substrate coordinates need equal, non-zero dimension".to_owned());
+        }
+        if !(0.0..1.0).contains(&self.weight_threshold) {
+            errors.push(format!(
+                "weight_threshold must be in [0, 1), got {}",
+                self.weight_threshold
+            ));
+        }
+        if !self.max_weight.is_finite() {
+            errors.push(format!(
+                "max_weight must be finite, got {}",
+                self.max_weight
+            ));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(NeatError::InvalidParameters(errors))
+        }
+    }
+
+    pub fn dimension(&self) -> Option<usize> {
+        let dimension = self.layers.first()?.first()?.len();
+        let consistent = self
+            .layers
+            .iter()
+            .flatten()
+            .all(|coord| coord.len() == dimension);
+
+        if consistent && dimension > 0 {
+            Some(dimension)
+        } else {
+            None
+        }
+    }
+
+    // CPPN receives coordinates of both endpoints of a connection
+    pub fn cppn_input_number(&self) -> Option<usize> {
+        Some(self.dimension()? * 2)
+    }
+
+    pub fn cppn_output_number(&self) -> usize {
+        1
+    }
+
+    fn scale_weight(&self, output: f64) -> f64 {
+        let magnitude = output.abs().min(1.0);
+        if magnitude <= self.weight_threshold {
+            0.0
+        } else {
+            output.signum() * (magnitude - self.weight_threshold) / (1.0 - self.weight_threshold)
+                * self.max_weight
+        }
+    }
+
+    // Queries the CPPN for the weight of every connection in the substrate
+    pub fn decode<T: Network>(&self, cppn: &mut T) -> Result<SubstrateNetwork, NeatError> {
+        // Fields are public, so they may have changed since construction
+        self.validate()?;
+        let cppn_input_number = self.cppn_input_number().unwrap();
+        if cppn.graph().output_number() != self.cppn_output_number() {
+            return Err(NeatError::InvalidParameters(vec![format!(
+                "CPPN needs {} output, got {}",
+                self.cppn_output_number(),
+                cppn.graph().output_number()
+            )]));
+        }
+
+        // Input number of the CPPN is checked by its activation
+        let mut cppn_inputs = Vec::with_capacity(cppn_input_number);
+        let mut weights = Vec::with_capacity(self.layers.len() - 1);
+        for pair in self.layers.windows(2) {
+            let (sources, targets) = (&pair[0], &pair[1]);
+            let mut layer_weights = Vec::with_capacity(sources.len() * targets.len());
+
+            for target in targets {
+                for source in sources {
+                    cppn_inputs.clear();
+                    cppn_inputs.extend_from_slice(source);
+                    cppn_inputs.extend_from_slice(target);
+
+                    let output = cppn.activate(&cppn_inputs)?[0];
+                    layer_weights.push(self.scale_weight(output));
+                }
+            }
+
+            weights.push(layer_weights);
+        }
+
+        Ok(SubstrateNetwork {
+            layer_sizes: self.layers.iter().map(|layer| layer.len()).collect(),
+            weights,
+            hidden_activation: self.hidden_activation,
+            output_activation: self.output_activation,
+        })
+    }
+}
+
+// Fixed-topology layered network decoded from a CPPN
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(try_from = "SubstrateNetworkData")]
+pub struct SubstrateNetwork {
+    layer_sizes: Vec<usize>,
+
+    // weights[l][j * (size of layer l) + i] connects node i of layer l to node j of layer l + 1
+    weights: Vec<Vec<f64>>,
+
+    hidden_activation: ActivationKind,
+    output_activation: ActivationKind,
+}
+
+// Deserialized form of a substrate network, whose weights are checked against the layers
+#[derive(Deserialize)]
+struct SubstrateNetworkData {
+    layer_sizes: Vec<usize>,
+    weights: Vec<Vec<f64>>,
+    hidden_activation: ActivationKind,
+    output_activation: ActivationKind,
+}
+
+impl TryFrom<SubstrateNetworkData> for SubstrateNetwork {
+    type Error = NeatError;
+
+    fn try_from(data: SubstrateNetworkData) -> Result<Self, Self::Error> {
+        if data.layer_sizes.len() < 2 || data.layer_sizes.contains(&0) {
+            return Err(NeatError::InvalidNetwork(format!(
+                "layer sizes {:?} aren't two or more non-empty layers",
+                data.layer_sizes
+            )));
+        }
+        if data.weights.len() + 1 != data.layer_sizes.len() {
+            return Err(NeatError::InvalidNetwork(format!(
+                "{} layers but {} weight layers",
+                data.layer_sizes.len(),
+                data.weights.len()
+            )));
+        }
+        for (l, layer_weights) in data.weights.iter().enumerate() {
+            let expected = data.layer_sizes[l] * data.layer_sizes[l + 1];
+            if layer_weights.len() != expected {
+                return Err(NeatError::InvalidNetwork(format!(
+                    "weight layer {} has {} weights instead of {}",
+                    l,
+                    layer_weights.len(),
+                    expected
+                )));
+            }
+        }
+
+        Ok(Self {
+            layer_sizes: data.layer_sizes,
+            weights: data.weights,
+            hidden_activation: data.hidden_activation,
+            output_activation: data.output_activation,
+        })
+    }
+}
+
+impl SubstrateNetwork {
+    pub fn activate(&self, inputs: &[f64]) -> Result<Vec<f64>, NeatError> {
+        if inputs.len() != self.layer_sizes[0] {
+            return Err(NeatError::InputSizeMismatch {
+                expected: self.layer_sizes[0],
+                actual: inputs.len(),
+            });
+        }
+
+        let mut values = inputs.to_vec();
+        for (l, layer_weights) in self.weights.iter().enumerate() {
+            let func = if l + 2 == self.layer_sizes.len() {
+                self.output_activation
+            } else {
+                self.hidden_activation
+            };
+
+            values = layer_weights
+                .chunks(values.len())
+                .map(|row| {
+                    let sum: f64 = row.iter().zip(values.iter()).map(|(w, v)| w * v).sum();
+                    activate(func, sum)
+                })
+                .collect();
+        }
+
+        Ok(values)
+    }
+
+    pub fn layer_sizes(&self) -> &[usize] {
+        &self.layer_sizes
+    }
+
+    pub fn weight(&self, layer: usize, source: usize, target: usize) -> f64 {
+        self.weights[layer][target * self.layer_sizes[layer] + source]
+    }
+
+    // Number of connections expressed with nonzero weight
+    pub fn connection_count(&self) -> usize {
+        self.weights.iter().flatten().filter(|&&w| w != 0.0).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        activations::sigmoid, innovation_record::InnovationRecord,
+        network::feedforward::Feedforward,
+    };
+    use float_cmp::approx_eq;
+
+    fn substrate() -> Substrate {
+        Substrate::new(
+            vec![
+                vec![vec![-1.0, -1.0], vec![1.0, -1.0]],
+                vec![vec![0.0, 1.0]],
+            ],
+            0.2,
+            3.0,
+            ActivationKind::Sigmoid,
+            ActivationKind::Sigmoid,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn substrate_should_be_decoded_with_thresholded_weights() {
+        let substrate = substrate();
+        let mut innov_record = InnovationRecord::new(4, 1);
+        // Linear CPPN summing every coordinate
+        let mut cppn = Feedforward::new(4, 1, ActivationKind::Linear, &mut innov_record);
+        cppn.graph_mut().edge_mut(0.into()).set_weight(0.0);
+        cppn.graph_mut().edge_mut(1.into()).set_weight(0.0);
+        cppn.graph_mut().edge_mut(2.into()).set_weight(0.4);
+
+        let network = substrate.decode(&mut cppn).unwrap();
+        assert_eq!(network.layer_sizes(), &[2, 1]);
+        // x2 * 0.4 = 0 for both sources, y2 = 1 is summed as is
+        assert!(approx_eq!(f64, network.weight(0, 0, 0), 3.0));
+        assert!(approx_eq!(f64, network.weight(0, 1, 0), 3.0));
+
+        cppn.graph_mut().edge_mut(3.into()).set_weight(0.1);
+        let network = substrate.decode(&mut cppn).unwrap();
+        assert_eq!(network.connection_count(), 0);
+        assert_eq!(network.activate(&[1.0, 1.0]).unwrap(), vec![sigmoid(0.0)]);
+    }
+
+    #[test]
+    fn substrate_network_should_propagate_through_layers() {
+        let mut substrate = substrate();
+        substrate.layers.insert(1, vec![vec![0.0, 0.0]]);
+        substrate.output_activation = ActivationKind::Linear;
+
+        let mut innov_record = InnovationRecord::new(4, 1);
+        let mut cppn = Feedforward::new(4, 1, ActivationKind::Clamped, &mut innov_record);
+        for i in 0..4 {
+            cppn.graph_mut().edge_mut(i.into()).set_weight(0.0);
+        }
+        cppn.mutate_add_connection(5.into(), 4.into(), 0.6, &mut innov_record);
+
+        let network = substrate.decode(&mut cppn).unwrap();
+        let hidden = sigmoid(1.5 * 0.5 + 1.5 * 0.25);
+        let output = network.activate(&[0.5, 0.25]).unwrap();
+        assert!(approx_eq!(f64, output[0], 1.5 * hidden, ulps = 2));
+        assert!(matches!(
+            network.activate(&[0.5]),
+            Err(NeatError::InputSizeMismatch {
+                expected: 2,
+                actual: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn invalid_substrate_network_should_not_be_deserialized() {
+        let mut innov_record = InnovationRecord::new(4, 1);
+        let mut cppn = Feedforward::new(4, 1, ActivationKind::Linear, &mut innov_record);
+        let network = substrate().decode(&mut cppn).unwrap();
+        let json = serde_json::to_value(&network).unwrap();
+        let loaded: SubstrateNetwork = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(
+            loaded.activate(&[1.0, 0.5]).unwrap(),
+            network.activate(&[1.0, 0.5]).unwrap()
+        );
+
+        let invalid_fields = [
+            ("layer_sizes", serde_json::json!([])),
+            ("layer_sizes", serde_json::json!([2, 0])),
+            ("layer_sizes", serde_json::json!([2, 1, 1])),
+            ("weights", serde_json::json!([[1.0]])),
+        ];
+        for (field, value) in &invalid_fields {
+            let mut json = json.clone();
+            json[field] = value.clone();
+            assert!(serde_json::from_value::<SubstrateNetwork>(json).is_err());
+        }
+    }
+
+    #[test]
+    fn cppn_with_wrong_input_number_should_not_be_decoded() {
+        let mut innov_record = InnovationRecord::new(6, 1);
+        let mut cppn = Feedforward::new(6, 1, ActivationKind::Linear, &mut innov_record);
+
+        assert!(matches!(
+            substrate().decode(&mut cppn),
+            Err(NeatError::InputSizeMismatch {
+                expected: 6,
+                actual: 4
+            })
+        ));
+    }
+
+    #[test]
+    fn invalid_substrate_should_be_rejected() {
+        let result = Substrate::new(
+            vec![vec![vec![0.0]], vec![]],
+            1.0,
+            3.0,
+            ActivationKind::Sigmoid,
+            ActivationKind::Sigmoid,
+        );
+        let errors = match result {
+            Err(NeatError::InvalidParameters(errors)) => errors,
+            _ => panic!("expected invalid parameters"),
+        };
+        assert_eq!(errors.len(), 2);
+        assert!(errors[1].starts_with("weight_threshold"));
+
+        let mut substrate = substrate();
+        substrate.weight_threshold = -0.5;
+        let mut innov_record = InnovationRecord::new(4, 1);
+        let mut cppn = Feedforward::new(4, 1, ActivationKind::Linear, &mut innov_record);
+        assert!(matches!(
+            substrate.decode(&mut cppn),
+            Err(NeatError::InvalidParameters(_))
+        ));
+    }
+}
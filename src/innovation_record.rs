@@ -1,10 +1,103 @@
 use std::collections::HashMap;
 
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct InnovationRecord {
     node_counter: usize,
     connection_counter: usize,
+    #[serde(
+        serialize_with = "serialize_connection_record",
+        deserialize_with = "deserialize_connection_record"
+    )]
     connection_record: HashMap<(usize, usize), usize>,
     species_counter: usize,
+
+    // Hidden node ids by innovation number of the connection split to add them
+    #[serde(
+        default,
+        serialize_with = "serialize_split_record",
+        deserialize_with = "deserialize_split_record"
+    )]
+    split_record: HashMap<usize, usize>,
+}
+
+// How long splits of the same connection keep producing the same hidden node id
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+pub enum SplitScope {
+    // For the whole run, like connection innovation numbers
+    #[default]
+    Run,
+
+    // Within a generation only, as in the original NEAT paper
+    Generation,
+}
+
+// Tuple keys are not allowed in JSON and TOML, so connections are stored as a list
+#[derive(Serialize, Deserialize)]
+struct ConnectionEntry {
+    source: usize,
+    target: usize,
+    innov_number: usize,
+}
+
+fn serialize_connection_record<S: Serializer>(
+    record: &HashMap<(usize, usize), usize>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut entries: Vec<ConnectionEntry> = record
+        .iter()
+        .map(|(&(source, target), &innov_number)| ConnectionEntry {
+            source,
+            target,
+            innov_number,
+        })
+        .collect();
+    entries.sort_by_key(|entry| entry.innov_number);
+
+    entries.serialize(serializer)
+}
+
+fn deserialize_connection_record<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<(usize, usize), usize>, D::Error> {
+    let entries = Vec::<ConnectionEntry>::deserialize(deserializer)?;
+    Ok(entries
+        .into_iter()
+        .map(|entry| ((entry.source, entry.target), entry.innov_number))
+        .collect())
+}
+
+#[derive(Serialize, Deserialize)]
+struct SplitEntry {
+    innov_number: usize,
+    node_id: usize,
+}
+
+fn serialize_split_record<S: Serializer>(
+    record: &HashMap<usize, usize>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut entries: Vec<SplitEntry> = record
+        .iter()
+        .map(|(&innov_number, &node_id)| SplitEntry {
+            innov_number,
+            node_id,
+        })
+        .collect();
+    entries.sort_by_key(|entry| entry.innov_number);
+
+    entries.serialize(serializer)
+}
+
+fn deserialize_split_record<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<usize, usize>, D::Error> {
+    let entries = Vec::<SplitEntry>::deserialize(deserializer)?;
+    Ok(entries
+        .into_iter()
+        .map(|entry| (entry.innov_number, entry.node_id))
+        .collect())
 }
 
 impl InnovationRecord {
@@ -14,6 +107,7 @@ impl InnovationRecord {
             connection_counter: 0,
             connection_record: HashMap::new(),
             species_counter: 0,
+            split_record: HashMap::new(),
         }
     }
 
@@ -22,6 +116,24 @@ impl InnovationRecord {
         self.node_counter - 1
     }
 
+    // Id of the hidden node splitting the connection, which is the same for every genome
+    // splitting it until the splits are cleared
+    pub fn split_node(&mut self, innov_number: usize) -> usize {
+        match self.split_record.get(&innov_number) {
+            None => {
+                let node_id = self.new_node();
+                self.split_record.insert(innov_number, node_id);
+                node_id
+            }
+            Some(&node_id) => node_id,
+        }
+    }
+
+    // Makes later splits produce new node ids, for splits scoped per generation
+    pub fn clear_splits(&mut self) {
+        self.split_record.clear();
+    }
+
     pub fn new_connection(&mut self, source: usize, target: usize) -> usize {
         match self.connection_record.get(&(source, target)) {
             None => {
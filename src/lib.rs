@@ -1,10 +1,20 @@
-mod activations;
+pub mod activations;
 mod species;
 
 pub mod edge_data;
+pub mod error;
+pub mod fitness;
+pub mod hyperneat;
 pub mod innovation_record;
 pub mod network;
 pub mod node_data;
 pub mod node_kind;
+pub mod novelty;
 pub mod parameters;
+pub mod pareto;
+pub mod phase;
 pub mod pool;
+pub mod reporter;
+pub mod serialization;
+pub mod stats;
+pub mod termination;
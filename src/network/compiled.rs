@@ -0,0 +1,399 @@
+use std::convert::TryFrom;
+
+use petgraph::{algo, visit::EdgeRef, EdgeDirection::Incoming};
+use serde::{Deserialize, Serialize};
+
+use super::network_graph::NetworkGraph;
+use crate::{
+    activations::{activate, ActivationKind},
+    error::NeatError,
+    node_kind::NodeKind,
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct CompiledNode {
+    kind: NodeKind,
+    activation: ActivationKind,
+
+    // Range of the incoming connections of the node in sources and weights
+    edge_start: usize,
+    edge_end: usize,
+}
+
+// Feedforward network flattened into arrays for deployment. Nodes are stored in topological
+// order and refer to each other by their position in that order, so activation is a single
+// pass over the arrays which doesn't touch the genome.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(try_from = "CompiledNetworkData")]
+pub struct CompiledNetwork {
+    nodes: Vec<CompiledNode>,
+
+    // Enabled incoming connections of every node, grouped by target node
+    sources: Vec<usize>,
+    weights: Vec<f64>,
+
+    input_positions: Vec<usize>,
+    output_positions: Vec<usize>,
+    bias_position: usize,
+}
+
+// Deserialized form of a compiled network, which is checked so that activation can't index
+// out of bounds
+#[derive(Deserialize)]
+struct CompiledNetworkData {
+    nodes: Vec<CompiledNode>,
+    sources: Vec<usize>,
+    weights: Vec<f64>,
+    input_positions: Vec<usize>,
+    output_positions: Vec<usize>,
+    bias_position: usize,
+}
+
+impl TryFrom<CompiledNetworkData> for CompiledNetwork {
+    type Error = NeatError;
+
+    fn try_from(data: CompiledNetworkData) -> Result<Self, Self::Error> {
+        if data.sources.len() != data.weights.len() {
+            return Err(NeatError::InvalidNetwork(format!(
+                "{} sources but {} weights",
+                data.sources.len(),
+                data.weights.len()
+            )));
+        }
+
+        let node_positions = data
+            .input_positions
+            .iter()
+            .map(|&position| (position, NodeKind::Input))
+            .chain(
+                data.output_positions
+                    .iter()
+                    .map(|&position| (position, NodeKind::Output)),
+            )
+            .chain(std::iter::once((data.bias_position, NodeKind::Bias)));
+        for (position, kind) in node_positions {
+            if data.nodes.get(position).map(|node| node.kind) != Some(kind) {
+                return Err(NeatError::InvalidNetwork(format!(
+                    "position {} isn't a node of kind {:?}",
+                    position, kind
+                )));
+            }
+        }
+
+        // Sources have to come before their target, as nodes are activated in order
+        let mut edge_start = 0;
+        for (position, node) in data.nodes.iter().enumerate() {
+            if node.edge_start != edge_start
+                || node.edge_end < node.edge_start
+                || node.edge_end > data.sources.len()
+                || data.sources[node.edge_start..node.edge_end]
+                    .iter()
+                    .any(|&source| source >= position)
+            {
+                return Err(NeatError::InvalidNetwork(format!(
+                    "connections of node {} are out of order",
+                    position
+                )));
+            }
+            edge_start = node.edge_end;
+        }
+
+        Ok(Self {
+            nodes: data.nodes,
+            sources: data.sources,
+            weights: data.weights,
+            input_positions: data.input_positions,
+            output_positions: data.output_positions,
+            bias_position: data.bias_position,
+        })
+    }
+}
+
+// Scratch space for activating a compiled network, reusable across activations
+#[derive(Clone, Debug)]
+pub struct ActivationState {
+    // None for hidden nodes which no activated node is connected to
+    values: Vec<Option<f64>>,
+}
+
+impl CompiledNetwork {
+    pub fn new(graph: &NetworkGraph) -> Result<Self, NeatError> {
+        let inner = graph.inner_data();
+        let order = algo::toposort(inner, None).map_err(|_| NeatError::CyclicGraph)?;
+
+        let mut positions = vec![0; order.len()];
+        for (position, index) in order.iter().enumerate() {
+            positions[index.index()] = position;
+        }
+
+        let mut nodes = Vec::with_capacity(order.len());
+        let mut sources = Vec::new();
+        let mut weights = Vec::new();
+        for &index in &order {
+            let edge_start = sources.len();
+
+            // Sorted by source position, so that inputs are summed in the same order as
+            // Feedforward propagates them
+            let mut incoming: Vec<(usize, f64)> = inner
+                .edges_directed(index, Incoming)
+                .filter(|edge| !edge.weight().is_disabled())
+                .map(|edge| (positions[edge.source().index()], edge.weight().get_weight()))
+                .collect();
+            incoming.sort_by_key(|&(source, _)| source);
+
+            for (source, weight) in incoming {
+                sources.push(source);
+                weights.push(weight);
+            }
+
+            let node = &inner[index];
+            nodes.push(CompiledNode {
+                kind: node.kind(),
+                activation: node.activation(),
+                edge_start,
+                edge_end: sources.len(),
+            });
+        }
+
+        let input_number = graph.input_number();
+        let output_number = graph.output_number();
+        let position_of = |index: usize| positions[index];
+
+        Ok(Self {
+            nodes,
+            sources,
+            weights,
+            input_positions: (0..input_number).map(position_of).collect(),
+            output_positions: (input_number..input_number + output_number)
+                .map(position_of)
+                .collect(),
+            bias_position: position_of(input_number + output_number),
+        })
+    }
+
+    pub fn input_number(&self) -> usize {
+        self.input_positions.len()
+    }
+
+    pub fn output_number(&self) -> usize {
+        self.output_positions.len()
+    }
+
+    pub fn state(&self) -> ActivationState {
+        ActivationState {
+            values: vec![None; self.nodes.len()],
+        }
+    }
+
+    pub fn activate(&self, inputs: &[f64]) -> Result<Vec<f64>, NeatError> {
+        let mut outputs = vec![0.0; self.output_number()];
+        self.activate_into(inputs, &mut outputs, &mut self.state())?;
+
+        Ok(outputs)
+    }
+
+    // Activates without allocating. Panics if outputs doesn't have output_number elements or
+    // the state was created by another network.
+    pub fn activate_into(
+        &self,
+        inputs: &[f64],
+        outputs: &mut [f64],
+        state: &mut ActivationState,
+    ) -> Result<(), NeatError> {
+        if inputs.len() != self.input_number() {
+            return Err(NeatError::InputSizeMismatch {
+                expected: self.input_number(),
+                actual: inputs.len(),
+            });
+        }
+        assert_eq!(outputs.len(), self.output_number());
+        assert_eq!(state.values.len(), self.nodes.len());
+
+        // Inputs and the bias are fed before any node is activated
+        let values = &mut state.values;
+        for value in values.iter_mut() {
+            *value = None;
+        }
+        for (&position, &input) in self.input_positions.iter().zip(inputs) {
+            values[position] = Some(input);
+        }
+        values[self.bias_position] = Some(1.0);
+
+        for (position, node) in self.nodes.iter().enumerate() {
+            let mut sum = values[position].unwrap_or(0.0);
+            let mut activated = false;
+            for edge in node.edge_start..node.edge_end {
+                if let Some(value) = values[self.sources[edge]] {
+                    sum += value * self.weights[edge];
+                    activated = true;
+                }
+            }
+
+            values[position] = match node.kind {
+                NodeKind::Input | NodeKind::Bias => Some(sum),
+                _ if activated => Some(activate(node.activation, sum)),
+                NodeKind::Output => Some(sum),
+                NodeKind::Hidden => None,
+            };
+        }
+
+        for (output, &position) in outputs.iter_mut().zip(&self.output_positions) {
+            *output = values[position].unwrap();
+        }
+
+        Ok(())
+    }
+
+    // Activates every row of inputs, which holds input_number values per row, and writes
+    // output_number values per row to outputs, which must have room for every row
+    pub fn activate_batch(&self, inputs: &[f64], outputs: &mut [f64]) -> Result<(), NeatError> {
+        let input_number = self.input_number();
+        let rows = inputs.len().checked_div(input_number).unwrap_or(0);
+        if rows * input_number != inputs.len() {
+            // Only the last row can be incomplete
+            return Err(NeatError::InputSizeMismatch {
+                expected: input_number,
+                actual: inputs.len() - rows * input_number,
+            });
+        }
+        if outputs.len() != rows * self.output_number() {
+            return Err(NeatError::InputSizeMismatch {
+                expected: rows * self.output_number(),
+                actual: outputs.len(),
+            });
+        }
+
+        let mut state = self.state();
+        for (input, output) in inputs
+            .chunks(input_number.max(1))
+            .zip(outputs.chunks_mut(self.output_number().max(1)))
+        {
+            self.activate_into(input, output, &mut state)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        innovation_record::InnovationRecord,
+        network::{feedforward::Feedforward, Network},
+    };
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    fn random_network(rng: &mut ChaCha8Rng) -> Feedforward {
+        let mut innov_record = InnovationRecord::new(3, 2);
+        let mut network = Feedforward::new(3, 2, ActivationKind::Sigmoid, &mut innov_record);
+        network.graph_mut().randomize_weights(-2.0, 2.0, rng);
+
+        for _ in 0..30 {
+            match rng.gen_range(0..3) {
+                0 => {
+                    let edge = network.graph().random_edge(rng).unwrap();
+                    network.mutate_add_node(edge, ActivationKind::Tanh, &mut innov_record);
+                }
+                1 => {
+                    let source = network.graph().random_node(rng);
+                    let target = network.graph().random_node(rng);
+                    let weight = rng.gen_range(-2.0..2.0);
+                    network.mutate_add_connection(source, target, weight, &mut innov_record);
+                }
+                _ => {
+                    let edge = network.graph().random_edge(rng).unwrap();
+                    network.mutate_toggle_connection(edge);
+                }
+            }
+        }
+
+        network
+    }
+
+    #[test]
+    fn compiled_network_should_match_feedforward() {
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        for _ in 0..20 {
+            let mut network = random_network(&mut rng);
+            let compiled = CompiledNetwork::new(network.graph()).unwrap();
+
+            for _ in 0..5 {
+                let inputs: Vec<f64> = (0..3).map(|_| rng.gen_range(-1.0..1.0)).collect();
+                assert_eq!(
+                    compiled.activate(&inputs).unwrap(),
+                    network.activate(&inputs).unwrap()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn batch_activation_should_match_single_activation() {
+        let mut rng = ChaCha8Rng::seed_from_u64(11);
+        let compiled = CompiledNetwork::new(random_network(&mut rng).graph()).unwrap();
+
+        let inputs = [0.5, -0.5, 1.0, 0.0, 0.25, -1.0];
+        let mut outputs = [0.0; 4];
+        compiled.activate_batch(&inputs, &mut outputs).unwrap();
+
+        assert_eq!(outputs[..2], compiled.activate(&inputs[..3]).unwrap()[..]);
+        assert_eq!(outputs[2..], compiled.activate(&inputs[3..]).unwrap()[..]);
+        assert!(matches!(
+            compiled.activate_batch(&inputs[..4], &mut outputs),
+            Err(NeatError::InputSizeMismatch {
+                expected: 3,
+                actual: 1
+            })
+        ));
+        assert!(matches!(
+            compiled.activate_batch(&inputs[..3], &mut outputs),
+            Err(NeatError::InputSizeMismatch {
+                expected: 2,
+                actual: 4
+            })
+        ));
+    }
+
+    #[test]
+    fn invalid_compiled_network_should_not_be_deserialized() {
+        let mut rng = ChaCha8Rng::seed_from_u64(13);
+        let compiled = CompiledNetwork::new(random_network(&mut rng).graph()).unwrap();
+        let json = serde_json::to_value(&compiled).unwrap();
+        let loaded: CompiledNetwork = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(
+            loaded.activate(&[0.5, -0.5, 1.0]).unwrap(),
+            compiled.activate(&[0.5, -0.5, 1.0]).unwrap()
+        );
+
+        let node_count = compiled.nodes.len();
+        let invalid_fields = [
+            ("bias_position", serde_json::json!(node_count)),
+            ("output_positions", serde_json::json!([0, 1])),
+            ("weights", serde_json::json!([1.0])),
+            (
+                "sources",
+                serde_json::json!(vec![node_count; compiled.sources.len()]),
+            ),
+        ];
+        for (field, value) in &invalid_fields {
+            let mut json = json.clone();
+            json[field] = value.clone();
+            assert!(serde_json::from_value::<CompiledNetwork>(json).is_err());
+        }
+    }
+
+    #[test]
+    fn cyclic_graph_should_not_be_compiled() {
+        let mut innov_record = InnovationRecord::new(1, 1);
+        let mut graph = NetworkGraph::new(1, 1, ActivationKind::Sigmoid, &mut innov_record);
+        graph.add_node(0.into(), ActivationKind::Sigmoid, &mut innov_record);
+        graph.add_connection(1.into(), 3.into(), 1.0, &mut innov_record);
+
+        assert!(matches!(
+            CompiledNetwork::new(&graph),
+            Err(NeatError::CyclicGraph)
+        ));
+    }
+}
@@ -1,57 +1,58 @@
 use petgraph::graph::NodeIndex;
+use serde::{Deserialize, Serialize};
 
-use super::{network_graph::NetworkGraph, Network};
+use super::{compiled::CompiledNetwork, network_graph::NetworkGraph, Network};
 use crate::{
-    activations::ActivationKind, innovation_record::InnovationRecord, node_data::NodeData,
+    activations::ActivationKind, error::NeatError, innovation_record::InnovationRecord,
     node_kind::NodeKind,
 };
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Feedforward {
-    graph: NetworkGraph,
     fitness: Option<f64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    objectives: Vec<f64>,
 
-    hidden_func: ActivationKind,
-    output_func: ActivationKind,
+    // TOML requires tables to come after plain values
+    graph: NetworkGraph,
 }
 
 impl Network for Feedforward {
     fn new(
         input_number: usize,
         output_number: usize,
-        hidden_func: ActivationKind,
         output_func: ActivationKind,
         innov_record: &mut InnovationRecord,
     ) -> Self {
         Self {
-            graph: NetworkGraph::new(input_number, output_number, innov_record),
+            graph: NetworkGraph::new(input_number, output_number, output_func, innov_record),
             fitness: None,
-            hidden_func,
-            output_func,
+            objectives: Vec::new(),
         }
     }
 
-    fn from_graph(
-        graph: NetworkGraph,
-        hidden_func: ActivationKind,
-        output_func: ActivationKind,
-    ) -> Self {
+    fn from_graph(graph: NetworkGraph) -> Self {
         Self {
             graph,
             fitness: None,
-            hidden_func,
-            output_func,
+            objectives: Vec::new(),
         }
     }
 
-    fn activate(&mut self, inputs: &[f64]) -> Option<Vec<f64>> {
-        let input_nodes: Vec<&mut NodeData> = self.graph.input_nodes_mut().collect();
-        if input_nodes.len() != inputs.len() {
-            return None;
+    fn activate(&mut self, inputs: &[f64]) -> Result<Vec<f64>, NeatError> {
+        let input_number = self.graph.input_number();
+        if inputs.len() != input_number {
+            return Err(NeatError::InputSizeMismatch {
+                expected: input_number,
+                actual: inputs.len(),
+            });
         }
 
+        // Checked before feeding inputs, so that node sums stay cleared on failure
+        let toposort = self.graph.toposort().ok_or(NeatError::CyclicGraph)?;
+
         // Set input to input nodes
-        for (node_data, &input) in input_nodes.into_iter().zip(inputs.iter()) {
+        for (node_data, &input) in self.graph.input_nodes_mut().zip(inputs.iter()) {
             node_data.add_input(input);
         }
 
@@ -59,17 +60,31 @@ impl Network for Feedforward {
         bias_node.add_input(1.0);
 
         // Activate nodes in topological order
-        let toposort = self.graph.toposort().unwrap();
         for node_index in toposort {
-            self.graph.activate_node(node_index, self.hidden_func);
+            self.graph.activate_node(node_index);
         }
 
-        let result = Some(self.graph.activate_output(self.output_func));
+        let result = Ok(self.graph.activate_output());
         self.graph.clear_sum();
 
         result
     }
 
+    // Compiles the graph once and reuses its activation buffer for every row
+    fn activate_batch(&mut self, inputs: &[&[f64]]) -> Result<Vec<Vec<f64>>, NeatError> {
+        let compiled = CompiledNetwork::new(&self.graph)?;
+        let mut state = compiled.state();
+
+        inputs
+            .iter()
+            .map(|row| {
+                let mut outputs = vec![0.0; compiled.output_number()];
+                compiled.activate_into(row, &mut outputs, &mut state)?;
+                Ok(outputs)
+            })
+            .collect()
+    }
+
     fn graph(&self) -> &NetworkGraph {
         &self.graph
     }
@@ -108,19 +123,34 @@ impl Network for Feedforward {
     }
 
     fn evaluate(&mut self, fitness: f64) {
-        self.fitness = Some(fitness.max(0.0));
+        // Non-finite fitness is kept as-is, so that the pool can reject it
+        self.fitness = Some(if fitness.is_finite() {
+            fitness.max(0.0)
+        } else {
+            fitness
+        });
     }
 
     fn fitness(&self) -> Option<f64> {
         self.fitness
     }
+
+    fn evaluate_objectives(&mut self, objectives: Vec<f64>) {
+        self.objectives = objectives;
+    }
+
+    fn objectives(&self) -> &[f64] {
+        &self.objectives
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::ActivationKind as Kind;
     use super::*;
     use crate::activations::sigmoid;
+    use std::cmp::Ordering;
 
     #[test]
     fn initial_network_activation_should_sum_input_and_squash() {
@@ -131,12 +161,11 @@ mod tests {
             input_number,
             output_number,
             Kind::Sigmoid,
-            Kind::Sigmoid,
             &mut innov_record,
         );
         assert_eq!(
-            network.activate(&[1.0, 2.0]),
-            Some(vec![sigmoid(1.0 + 2.0)])
+            network.activate(&[1.0, 2.0]).unwrap(),
+            vec![sigmoid(1.0 + 2.0)]
         );
     }
 
@@ -146,14 +175,19 @@ mod tests {
         let output_number = 1;
 
         let mut innov_record = InnovationRecord::new(input_number, output_number);
-        let mut graph = NetworkGraph::new(input_number, output_number, &mut innov_record);
-        graph.add_node(0.into(), &mut innov_record);
+        let mut graph = NetworkGraph::new(
+            input_number,
+            output_number,
+            Kind::Sigmoid,
+            &mut innov_record,
+        );
+        graph.add_node(0.into(), Kind::Sigmoid, &mut innov_record);
         graph.remove_connetion(1.into());
 
-        let mut network = Feedforward::from_graph(graph, Kind::Sigmoid, Kind::Sigmoid);
+        let mut network = Feedforward::from_graph(graph);
 
-        assert_eq!(network.activate(&[0.0]), Some(vec![0.0]));
-        assert_eq!(network.activate(&[1.0]), Some(vec![0.0]));
+        assert_eq!(network.activate(&[0.0]).unwrap(), vec![0.0]);
+        assert_eq!(network.activate(&[1.0]).unwrap(), vec![0.0]);
     }
 
     #[test]
@@ -165,14 +199,13 @@ mod tests {
             input_number,
             output_number,
             Kind::Sigmoid,
-            Kind::Sigmoid,
             &mut innov_record,
         );
-        assert!(network.mutate_add_node(0.into(), &mut innov_record));
+        assert!(network.mutate_add_node(0.into(), Kind::Sigmoid, &mut innov_record));
 
         assert_eq!(
-            network.activate(&[1.0, 2.0]),
-            Some(vec![sigmoid(sigmoid(1.0) + 2.0)])
+            network.activate(&[1.0, 2.0]).unwrap(),
+            vec![sigmoid(sigmoid(1.0) + 2.0)]
         );
     }
 
@@ -181,16 +214,58 @@ mod tests {
         let input_number = 2;
         let output_number = 1;
         let mut innov_record = InnovationRecord::new(input_number, output_number);
-        let mut network = Feedforward::new(2, 1, Kind::Sigmoid, Kind::Sigmoid, &mut innov_record);
+        let mut network = Feedforward::new(2, 1, Kind::Sigmoid, &mut innov_record);
 
         assert_eq!(
-            network.activate(&[1.0, 2.0]),
-            Some(vec![sigmoid(1.0 + 2.0)])
+            network.activate(&[1.0, 2.0]).unwrap(),
+            vec![sigmoid(1.0 + 2.0)]
+        );
+        assert_eq!(
+            network.activate(&[1.0, 2.0]).unwrap(),
+            vec![sigmoid(1.0 + 2.0)]
         );
+    }
+
+    #[test]
+    fn batch_activation_should_match_activation_of_each_row() {
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let mut network = Feedforward::new(2, 1, Kind::Sigmoid, &mut innov_record);
+        assert!(network.mutate_add_node(0.into(), Kind::Tanh, &mut innov_record));
+
+        let inputs: [&[f64]; 3] = [&[1.0, 2.0], &[0.0, -1.0], &[0.5, 0.5]];
+        let expected: Vec<Vec<f64>> = inputs
+            .iter()
+            .map(|row| network.activate(row).unwrap())
+            .collect();
+        assert_eq!(network.activate_batch(&inputs).unwrap(), expected);
+        assert!(matches!(
+            network.activate_batch(&[&[1.0, 2.0], &[1.0]]),
+            Err(NeatError::InputSizeMismatch {
+                expected: 2,
+                actual: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn simpler_network_should_win_tie_in_parsimonious_comparison() {
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let mut simple = Feedforward::new(2, 1, Kind::Sigmoid, &mut innov_record);
+        let mut complex = simple.clone();
+        assert!(complex.mutate_add_node(0.into(), Kind::Sigmoid, &mut innov_record));
+        simple.evaluate(1.0);
+        complex.evaluate(1.0);
+
+        assert_eq!(simple.complexity(), 2);
+        assert_eq!(complex.complexity(), 4);
+        assert_eq!(simple.compare(&complex), Some(Ordering::Equal));
         assert_eq!(
-            network.activate(&[1.0, 2.0]),
-            Some(vec![sigmoid(1.0 + 2.0)])
+            simple.compare_parsimonious(&complex),
+            Some(Ordering::Greater)
         );
+
+        complex.evaluate(2.0);
+        assert_eq!(simple.compare_parsimonious(&complex), Some(Ordering::Less));
     }
 
     #[test]
@@ -202,12 +277,11 @@ mod tests {
             input_number,
             output_number,
             Kind::Sigmoid,
-            Kind::Sigmoid,
             &mut innov_record,
         );
         assert!(network.mutate_add_connection(3.into(), 2.into(), -3.0, &mut innov_record));
 
-        assert_eq!(network.activate(&[1.0, 2.0]), Some(vec![sigmoid(0.0)]));
+        assert_eq!(network.activate(&[1.0, 2.0]).unwrap(), vec![sigmoid(0.0)]);
     }
 
     #[test]
@@ -215,13 +289,13 @@ mod tests {
         let input_number = 2;
         let output_number = 1;
         let mut innov_record = InnovationRecord::new(input_number, output_number);
-        let mut network = Feedforward::new(2, 1, Kind::Sigmoid, Kind::Sigmoid, &mut innov_record);
-        assert!(network.mutate_add_node(0.into(), &mut innov_record));
+        let mut network = Feedforward::new(2, 1, Kind::Sigmoid, &mut innov_record);
+        assert!(network.mutate_add_node(0.into(), Kind::Sigmoid, &mut innov_record));
         assert!(network.mutate_add_connection(1.into(), 4.into(), 2.0, &mut innov_record));
 
         assert_eq!(
-            network.activate(&[1.0, 2.0]),
-            Some(vec![sigmoid(sigmoid(5.0) + 2.0)])
+            network.activate(&[1.0, 2.0]).unwrap(),
+            vec![sigmoid(sigmoid(5.0) + 2.0)]
         );
     }
 
@@ -230,7 +304,7 @@ mod tests {
         let input_number = 2;
         let output_number = 1;
         let mut innov_record = InnovationRecord::new(input_number, output_number);
-        let mut network = Feedforward::new(2, 1, Kind::Sigmoid, Kind::Sigmoid, &mut innov_record);
+        let mut network = Feedforward::new(2, 1, Kind::Sigmoid, &mut innov_record);
         assert_eq!(
             network.mutate_add_connection(0.into(), 2.into(), 1.0, &mut innov_record),
             false
@@ -246,11 +320,10 @@ mod tests {
             input_number,
             output_number,
             Kind::Sigmoid,
-            Kind::Sigmoid,
             &mut innov_record,
         );
-        assert!(network.mutate_add_node(0.into(), &mut innov_record));
-        assert!(network.mutate_add_node(1.into(), &mut innov_record));
+        assert!(network.mutate_add_node(0.into(), Kind::Sigmoid, &mut innov_record));
+        assert!(network.mutate_add_node(1.into(), Kind::Sigmoid, &mut innov_record));
         assert!(network.mutate_add_connection(4.into(), 5.into(), 1.0, &mut innov_record));
         assert_eq!(
             network.mutate_add_connection(5.into(), 4.into(), 1.0, &mut innov_record),
@@ -267,10 +340,9 @@ mod tests {
             input_number,
             output_number,
             Kind::Sigmoid,
-            Kind::Sigmoid,
             &mut innov_record,
         );
-        assert!(network.mutate_add_node(0.into(), &mut innov_record));
+        assert!(network.mutate_add_node(0.into(), Kind::Sigmoid, &mut innov_record));
         assert_eq!(
             network.mutate_add_connection(2.into(), 4.into(), 1.0, &mut innov_record),
             false
@@ -286,4 +358,34 @@ mod tests {
             false
         );
     }
+
+    #[test]
+    fn mutate_activation_should_change_only_hidden_node() {
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let mut network = Feedforward::new(2, 1, Kind::Sigmoid, &mut innov_record);
+        assert!(network.mutate_add_node(0.into(), Kind::Sigmoid, &mut innov_record));
+
+        assert!(!network.mutate_activation(2.into(), Kind::Relu));
+        assert!(!network.mutate_activation(4.into(), Kind::Sigmoid));
+        assert!(network.mutate_activation(4.into(), Kind::Relu));
+
+        assert_eq!(
+            network.activate(&[-1.0, 2.0]).unwrap(),
+            vec![sigmoid(0.0 + 2.0)]
+        );
+    }
+
+    #[test]
+    fn cyclic_graph_activation_should_fail() {
+        let mut innov_record = InnovationRecord::new(1, 1);
+        let mut network = Feedforward::new(1, 1, Kind::Sigmoid, &mut innov_record);
+        network
+            .graph_mut()
+            .add_connection(1.into(), 1.into(), 1.0, &mut innov_record);
+
+        assert!(matches!(
+            network.activate(&[1.0]),
+            Err(NeatError::CyclicGraph)
+        ));
+    }
 }
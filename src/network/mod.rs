@@ -1,29 +1,32 @@
 use std::cmp::Ordering;
 
 use petgraph::graph::{EdgeIndex, NodeIndex};
+use rand::RngCore;
 
 use self::network_graph::NetworkGraph;
 use crate::node_kind::NodeKind;
-use crate::{activations::ActivationKind, innovation_record::InnovationRecord};
+use crate::{activations::ActivationKind, error::NeatError, innovation_record::InnovationRecord};
 
+pub mod compiled;
 pub mod feedforward;
 pub mod network_graph;
+pub mod recurrent;
 
 pub trait Network {
     fn new(
         input_number: usize,
         output_number: usize,
-        hidden_func: ActivationKind,
         output_func: ActivationKind,
         innov_record: &mut InnovationRecord,
     ) -> Self;
-    fn from_graph(
-        graph: NetworkGraph,
-        hidden_func: ActivationKind,
-        output_func: ActivationKind,
-    ) -> Self;
+    fn from_graph(graph: NetworkGraph) -> Self;
+
+    fn activate(&mut self, inputs: &[f64]) -> Result<Vec<f64>, NeatError>;
 
-    fn activate(&mut self, inputs: &[f64]) -> Option<Vec<f64>>;
+    // Activates the network once for each row of inputs, in order
+    fn activate_batch(&mut self, inputs: &[&[f64]]) -> Result<Vec<Vec<f64>>, NeatError> {
+        inputs.iter().map(|row| self.activate(row)).collect()
+    }
 
     fn graph(&self) -> &NetworkGraph;
     fn graph_mut(&mut self) -> &mut NetworkGraph;
@@ -47,8 +50,13 @@ pub trait Network {
         true
     }
 
-    fn mutate_add_node(&mut self, index: EdgeIndex, innov_record: &mut InnovationRecord) -> bool {
-        self.graph_mut().add_node(index, innov_record);
+    fn mutate_add_node(
+        &mut self,
+        index: EdgeIndex,
+        activation: ActivationKind,
+        innov_record: &mut InnovationRecord,
+    ) -> bool {
+        self.graph_mut().add_node(index, activation, innov_record);
         true
     }
 
@@ -66,6 +74,16 @@ pub trait Network {
         }
     }
 
+    fn mutate_activation(&mut self, index: NodeIndex, activation: ActivationKind) -> bool {
+        let node = self.graph_mut().node_mut(index);
+        if node.kind() == NodeKind::Hidden && node.activation() != activation {
+            node.set_activation(activation);
+            true
+        } else {
+            false
+        }
+    }
+
     fn mutate_assign_weight(&mut self, index: EdgeIndex, weight: f64) -> bool {
         let edge = self.graph_mut().edge_mut(index);
         edge.set_weight(weight);
@@ -78,38 +96,57 @@ pub trait Network {
         true
     }
 
-    fn crossover(
-        &self,
-        other: &Self,
-        hidden_func: ActivationKind,
-        output_func: ActivationKind,
-    ) -> Option<Self>
+    fn crossover(&self, other: &Self, rng: &mut impl RngCore) -> Result<Self, NeatError>
     where
         Self: Sized,
     {
-        let (my_fitness, other_fitness) = (self.fitness()?, other.fitness()?);
-        let rng = &mut rand::thread_rng();
+        let (my_fitness, other_fitness) = match (self.fitness(), other.fitness()) {
+            (Some(my_fitness), Some(other_fitness)) => (my_fitness, other_fitness),
+            _ => {
+                return Err(NeatError::IncompatibleCrossover(
+                    "parent hasn't been evaluated".to_owned(),
+                ))
+            }
+        };
         let new_graph = self
             .graph()
-            .crossover(&other.graph(), my_fitness >= other_fitness, rng)?;
+            .crossover(other.graph(), my_fitness >= other_fitness, rng)?;
+
+        let mut offspring = Self::from_graph(new_graph);
+        offspring.set_steps(self.steps());
+        Ok(offspring)
+    }
 
-        Some(Self::from_graph(new_graph, hidden_func, output_func))
+    // Propagation steps per activation, which networks propagating in a single pass ignore
+    fn steps(&self) -> usize {
+        1
     }
+    fn set_steps(&mut self, _steps: usize) {}
 
     fn evaluate(&mut self, fitness: f64);
     fn fitness(&self) -> Option<f64>;
 
+    // Objectives of multi-objective evaluation, every one of which is maximized. Networks
+    // which don't keep them can't be evaluated by multiple objectives.
+    fn evaluate_objectives(&mut self, _objectives: Vec<f64>) {}
+    fn objectives(&self) -> &[f64] {
+        &[]
+    }
+
     fn compare(&self, other: &Self) -> Option<Ordering> {
         self.fitness().partial_cmp(&other.fitness())
-        /*
-        let result = self.fitness().partial_cmp(&other.fitness());
-        if result == Some(Ordering::Equal) {
-            let my_complexity = self.graph().edge_count();
-            let other_complexity = other.graph().edge_count();
-            other_complexity.partial_cmp(&my_complexity)
-        } else {
-            result
+    }
+
+    // Same as compare, except that the simpler one of genomes with equal fitness is greater
+    fn compare_parsimonious(&self, other: &Self) -> Option<Ordering> {
+        match self.compare(other) {
+            Some(Ordering::Equal) => Some(other.complexity().cmp(&self.complexity())),
+            result => result,
         }
-        */
+    }
+
+    // Number of hidden nodes and enabled edges
+    fn complexity(&self) -> usize {
+        self.graph().hidden_node_count() + self.graph().enabled_edge_count()
     }
 }
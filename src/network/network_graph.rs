@@ -1,5 +1,6 @@
 use std::{
     collections::HashMap,
+    convert::TryFrom,
     fmt::{self, Display},
 };
 
@@ -11,12 +12,14 @@ use rand::{
     distributions::{Bernoulli, Distribution, Uniform},
     RngCore,
 };
+use serde::{Deserialize, Serialize};
 
 use crate::node_kind::NodeKind;
 use crate::{activations::ActivationKind, node_data::NodeData};
-use crate::{edge_data::EdgeData, innovation_record::InnovationRecord};
+use crate::{edge_data::EdgeData, error::NeatError, innovation_record::InnovationRecord};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(into = "GenomeData", try_from = "GenomeData")]
 pub struct NetworkGraph {
     graph: DiGraph<NodeData, EdgeData>,
     input_number: usize,
@@ -24,6 +27,25 @@ pub struct NetworkGraph {
     toposort_cache: Option<Vec<NodeIndex>>,
 }
 
+// Serialized form of a network graph, which refers to nodes by their ids rather than
+// petgraph indices so that the representation stays stable
+#[derive(Serialize, Deserialize)]
+struct GenomeData {
+    input_number: usize,
+    output_number: usize,
+    nodes: Vec<NodeData>,
+    edges: Vec<EdgeGene>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EdgeGene {
+    source: usize,
+    target: usize,
+    weight: f64,
+    disabled: bool,
+    innov_number: usize,
+}
+
 type DiffResult<'a> = (
     Vec<(&'a Edge<EdgeData>, &'a Edge<EdgeData>)>,
     Vec<&'a Edge<EdgeData>>,
@@ -32,18 +54,31 @@ type DiffResult<'a> = (
 
 impl NetworkGraph {
     // Creates a new network graph, without connections
-    pub fn new_disconnected(input_number: usize, output_number: usize) -> Self {
+    pub fn new_disconnected(
+        input_number: usize,
+        output_number: usize,
+        output_func: ActivationKind,
+    ) -> Self {
         let mut graph = DiGraph::new();
 
+        // Activation of input and bias nodes is never applied
         for i in 0..input_number {
-            graph.add_node(NodeData::new(NodeKind::Input, i));
+            graph.add_node(NodeData::new(NodeKind::Input, i, ActivationKind::Linear));
         }
 
         for i in 0..output_number {
-            graph.add_node(NodeData::new(NodeKind::Output, input_number + i));
+            graph.add_node(NodeData::new(
+                NodeKind::Output,
+                input_number + i,
+                output_func,
+            ));
         }
 
-        graph.add_node(NodeData::new(NodeKind::Bias, input_number + output_number));
+        graph.add_node(NodeData::new(
+            NodeKind::Bias,
+            input_number + output_number,
+            ActivationKind::Linear,
+        ));
 
         Self {
             graph,
@@ -57,9 +92,10 @@ impl NetworkGraph {
     pub fn new(
         input_number: usize,
         output_number: usize,
+        output_func: ActivationKind,
         innov_record: &mut InnovationRecord,
     ) -> Self {
-        let mut network = NetworkGraph::new_disconnected(input_number, output_number);
+        let mut network = NetworkGraph::new_disconnected(input_number, output_number, output_func);
 
         for i in 0..input_number {
             for j in 0..output_number {
@@ -112,11 +148,11 @@ impl NetworkGraph {
     }
 
     // Collect activation results of output nodes and return them
-    pub fn activate_output(&self, func: ActivationKind) -> Vec<f64> {
+    pub fn activate_output(&self) -> Vec<f64> {
         let mut result = Vec::new();
         for index in self.input_number..self.input_number + self.output_number {
             let node = &self.graph[NodeIndex::new(index)];
-            result.push(node.activate(func).unwrap());
+            result.push(node.activate().unwrap());
         }
 
         result
@@ -150,6 +186,14 @@ impl NetworkGraph {
         self.graph.edge_count()
     }
 
+    pub fn enabled_edge_count(&self) -> usize {
+        self.graph
+            .raw_edges()
+            .iter()
+            .filter(|edge| !edge.weight.is_disabled())
+            .count()
+    }
+
     pub fn random_edge(&self, rng: &mut impl RngCore) -> Option<EdgeIndex> {
         if self.graph.edge_count() == 0 {
             None
@@ -164,6 +208,17 @@ impl NetworkGraph {
         NodeIndex::new(uniform.sample(rng))
     }
 
+    pub fn random_hidden_node(&self, rng: &mut impl RngCore) -> Option<NodeIndex> {
+        // Input, output and bias nodes are never removed, so hidden nodes always come after them
+        let default_count = self.input_number + self.output_number + 1;
+        if self.graph.node_count() == default_count {
+            None
+        } else {
+            let uniform = Uniform::from(default_count..self.graph.node_count());
+            Some(NodeIndex::new(uniform.sample(rng)))
+        }
+    }
+
     pub fn has_connection(&self, source: NodeIndex, target: NodeIndex) -> bool {
         self.graph.contains_edge(source, target)
     }
@@ -173,13 +228,12 @@ impl NetworkGraph {
     }
 
     // Propagate output of the node to outgoing connections
-    pub fn activate_node(&mut self, index: NodeIndex, func: ActivationKind) {
-        let activation;
-        match self.graph[index].activate(func) {
+    pub fn activate_node(&mut self, index: NodeIndex) {
+        let activation = match self.graph[index].activate() {
             // Check if every incoming connection has been propagated
-            Some(v) => activation = v,
+            Some(v) => v,
             None => return,
-        }
+        };
         let mut neighbors = self.graph.neighbors(index).detach();
 
         while let Some((edge_index, target_index)) = neighbors.next(&self.graph) {
@@ -207,7 +261,12 @@ impl NetworkGraph {
         self.toposort_cache.clone()
     }
 
-    pub fn add_node(&mut self, edge: EdgeIndex, innov_record: &mut InnovationRecord) -> NodeIndex {
+    pub fn add_node(
+        &mut self,
+        edge: EdgeIndex,
+        activation: ActivationKind,
+        innov_record: &mut InnovationRecord,
+    ) -> NodeIndex {
         let previous_weight: f64;
         let new_node_index: NodeIndex;
         let new_node_id: usize;
@@ -216,11 +275,23 @@ impl NetworkGraph {
             let edge_data = self.graph.edge_weight_mut(edge).unwrap();
             edge_data.set_disabled(true);
             previous_weight = edge_data.get_weight();
+            let split_id = innov_record.split_node(edge_data.innov_number());
 
-            new_node_id = innov_record.new_node();
-            new_node_index = self
+            // A genome that already has the node of this split, e.g. by splitting the
+            // connection again after re-enabling it, needs a distinct one
+            new_node_id = if self
                 .graph
-                .add_node(NodeData::new(NodeKind::Hidden, new_node_id));
+                .raw_nodes()
+                .iter()
+                .any(|n| n.weight.id() == split_id)
+            {
+                innov_record.new_node()
+            } else {
+                split_id
+            };
+            new_node_index =
+                self.graph
+                    .add_node(NodeData::new(NodeKind::Hidden, new_node_id, activation));
         }
 
         let (source, target) = self.graph.edge_endpoints(edge).unwrap();
@@ -294,6 +365,16 @@ impl NetworkGraph {
         (edge.clone(), source, target)
     }
 
+    fn hidden_nodes_by_id(&self) -> HashMap<usize, &NodeData> {
+        self.graph
+            .raw_nodes()
+            .iter()
+            .map(|node| &node.weight)
+            .filter(|data| data.kind() == NodeKind::Hidden)
+            .map(|data| (data.id(), data))
+            .collect()
+    }
+
     fn union_difference<'a>(&'a self, other: &'a NetworkGraph) -> DiffResult<'a> {
         let mut matching: Vec<(&Edge<EdgeData>, &Edge<EdgeData>)> = Vec::new();
         let mut my_mismatch: Vec<&Edge<EdgeData>> = Vec::new();
@@ -312,10 +393,10 @@ impl NetworkGraph {
             }
 
             if let Some(i) = matched {
-                matching.push((&my_edge, &other_edges[i]));
+                matching.push((my_edge, other_edges[i]));
                 other_edges.remove(i);
             } else {
-                my_mismatch.push(&my_edge);
+                my_mismatch.push(my_edge);
             }
         }
 
@@ -327,12 +408,25 @@ impl NetworkGraph {
         other: &NetworkGraph,
         more_fit: bool,
         rng: &mut impl RngCore,
-    ) -> Option<NetworkGraph> {
+    ) -> Result<NetworkGraph, NeatError> {
         if self.input_number != other.input_number || self.output_number != other.output_number {
-            return None;
+            return Err(NeatError::IncompatibleCrossover(format!(
+                "parents have {}/{} and {}/{} input/output nodes",
+                self.input_number, self.output_number, other.input_number, other.output_number
+            )));
+        }
+
+        let mut network = NetworkGraph::new_disconnected(
+            self.input_number,
+            self.output_number,
+            ActivationKind::Linear,
+        );
+        // Output nodes are inherited as-is, since their activations never mutate
+        for index in self.input_number..self.input_number + self.output_number {
+            let index = NodeIndex::new(index);
+            network.graph[index] = self.graph[index].clone();
         }
 
-        let mut network = NetworkGraph::new_disconnected(self.input_number, self.output_number);
         let mut new_genes = Vec::new();
 
         let (matching, my_mismatch, other_mismatch) = self.union_difference(other);
@@ -356,6 +450,9 @@ impl NetworkGraph {
             }
         }
 
+        let my_nodes = self.hidden_nodes_by_id();
+        let other_nodes = other.hidden_nodes_by_id();
+
         // node_map is used to prevent adding nodes with the same innovation number
         let mut node_map: HashMap<usize, NodeIndex> = HashMap::new();
         let mut get_index = |data: &NodeData, network: &mut Self| {
@@ -365,6 +462,19 @@ impl NetworkGraph {
 
             match node_map.get(&data.id()) {
                 None => {
+                    let mut data = data.clone();
+                    // Node present in both parents inherits activation of either one randomly
+                    if let (Some(my_node), Some(other_node)) =
+                        (my_nodes.get(&data.id()), other_nodes.get(&data.id()))
+                    {
+                        let parent = if dist.sample(rng) {
+                            my_node
+                        } else {
+                            other_node
+                        };
+                        data.set_activation(parent.activation());
+                    }
+
                     let index = network.graph.add_node(data.clone());
                     node_map.insert(data.id(), index);
                     index
@@ -381,10 +491,10 @@ impl NetworkGraph {
                 .add_edge(source_index, target_index, gene.weight.clone());
         }
 
-        Some(network)
+        Ok(network)
     }
 
-    pub fn compatibility_metric(&self, other: &NetworkGraph, c1: f64, c2: f64) -> f64 {
+    pub fn compatibility_metric(&self, other: &NetworkGraph, c1: f64, c2: f64, c3: f64) -> f64 {
         let (matching, my_mismatch, other_mismatch) = self.union_difference(other);
         let mut weight_difference = 0.0;
 
@@ -393,10 +503,204 @@ impl NetworkGraph {
                 (my_edge_data.weight.get_weight() - other_edge_data.weight.get_weight()).abs();
         }
 
+        // Number of hidden nodes present in both networks with different activations
+        let other_nodes = other.hidden_nodes_by_id();
+        let activation_difference = self
+            .hidden_nodes_by_id()
+            .into_iter()
+            .filter(|(id, data)| {
+                other_nodes
+                    .get(id)
+                    .is_some_and(|other_data| other_data.activation() != data.activation())
+            })
+            .count();
+
         let mismatch_count = my_mismatch.len() + other_mismatch.len();
         let n = std::cmp::max(self.graph.edge_count(), other.graph.edge_count());
 
-        (mismatch_count as f64) * c1 / (n as f64) + weight_difference * c2
+        (mismatch_count as f64) * c1 / (n as f64)
+            + weight_difference * c2
+            + (activation_difference as f64) * c3
+    }
+
+    // Graphviz representation of the network, which can be rendered with `dot -Tsvg`
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        self.write_dot(&mut dot).unwrap();
+        dot
+    }
+
+    // Inputs and bias are ranked in the first layer and outputs in the last one. Edges are
+    // blue when positive and red when negative, thicker the larger their weight, dashed when
+    // disabled, and labeled with their innovation number.
+    pub fn write_dot(&self, w: &mut impl fmt::Write) -> fmt::Result {
+        writeln!(w, "digraph {{")?;
+        writeln!(w, "    rankdir=LR;")?;
+        writeln!(w, "    node [shape=circle];")?;
+
+        writeln!(w, "    {{")?;
+        writeln!(w, "        rank=source;")?;
+        for data in self.graph.raw_nodes().iter().map(|node| &node.weight) {
+            match data.kind() {
+                NodeKind::Input => writeln!(
+                    w,
+                    "        n{} [label=\"in {}\", shape=box];",
+                    data.id(),
+                    data.id()
+                )?,
+                NodeKind::Bias => {
+                    writeln!(w, "        n{} [label=\"bias\", shape=box];", data.id())?
+                }
+                _ => {}
+            }
+        }
+        writeln!(w, "    }}")?;
+
+        writeln!(w, "    {{")?;
+        writeln!(w, "        rank=sink;")?;
+        for data in self.graph.raw_nodes().iter().map(|node| &node.weight) {
+            if data.kind() == NodeKind::Output {
+                writeln!(
+                    w,
+                    "        n{} [label=\"out {}\\n{}\", shape=doublecircle];",
+                    data.id(),
+                    data.id(),
+                    data.activation()
+                )?;
+            }
+        }
+        writeln!(w, "    }}")?;
+
+        for data in self.graph.raw_nodes().iter().map(|node| &node.weight) {
+            if data.kind() == NodeKind::Hidden {
+                writeln!(
+                    w,
+                    "    n{} [label=\"{}\\n{}\"];",
+                    data.id(),
+                    data.id(),
+                    data.activation()
+                )?;
+            }
+        }
+
+        for edge in self.graph.raw_edges() {
+            let weight = edge.weight.get_weight();
+            writeln!(
+                w,
+                "    n{} -> n{} [label=\"#{}\", tooltip=\"{:.4}\", color=\"{}\", penwidth={:.2}{}];",
+                self.graph[edge.source()].id(),
+                self.graph[edge.target()].id(),
+                edge.weight.innov_number(),
+                weight,
+                if weight >= 0.0 { "#1f77b4" } else { "#d62728" },
+                (0.5 + weight.abs()).min(5.0),
+                if edge.weight.is_disabled() {
+                    ", style=dashed"
+                } else {
+                    ""
+                }
+            )?;
+        }
+
+        writeln!(w, "}}")
+    }
+}
+
+impl From<NetworkGraph> for GenomeData {
+    fn from(network: NetworkGraph) -> Self {
+        let nodes = network
+            .graph
+            .raw_nodes()
+            .iter()
+            .map(|node| node.weight.clone())
+            .collect();
+        let edges = network
+            .graph
+            .raw_edges()
+            .iter()
+            .map(|edge| EdgeGene {
+                source: network.graph[edge.source()].id(),
+                target: network.graph[edge.target()].id(),
+                weight: edge.weight.get_weight(),
+                disabled: edge.weight.is_disabled(),
+                innov_number: edge.weight.innov_number(),
+            })
+            .collect();
+
+        Self {
+            input_number: network.input_number,
+            output_number: network.output_number,
+            nodes,
+            edges,
+        }
+    }
+}
+
+impl TryFrom<GenomeData> for NetworkGraph {
+    type Error = String;
+
+    fn try_from(data: GenomeData) -> Result<Self, Self::Error> {
+        let default_count = data.input_number + data.output_number + 1;
+        let mut network = NetworkGraph::new_disconnected(
+            data.input_number,
+            data.output_number,
+            ActivationKind::Linear,
+        );
+
+        if data.nodes.len() < default_count {
+            return Err(format!(
+                "expected at least {} nodes, found {}",
+                default_count,
+                data.nodes.len()
+            ));
+        }
+
+        // Input, output and bias nodes must come first, with index same as ID
+        for (i, node) in data.nodes.iter().take(default_count).enumerate() {
+            let default_node = &mut network.graph[NodeIndex::new(i)];
+            if default_node.kind() != node.kind() || default_node.id() != node.id() {
+                return Err(format!(
+                    "unexpected {:?} node with id {}",
+                    node.kind(),
+                    node.id()
+                ));
+            }
+            default_node.set_activation(node.activation());
+        }
+
+        let mut node_map: HashMap<usize, NodeIndex> =
+            (0..default_count).map(|i| (i, NodeIndex::new(i))).collect();
+        for node in data.nodes.into_iter().skip(default_count) {
+            if node.kind() != NodeKind::Hidden {
+                return Err(format!(
+                    "unexpected {:?} node with id {}",
+                    node.kind(),
+                    node.id()
+                ));
+            }
+
+            let id = node.id();
+            let index = network.graph.add_node(node);
+            if node_map.insert(id, index).is_some() {
+                return Err(format!("duplicate node id {}", id));
+            }
+        }
+
+        for gene in data.edges {
+            let get_index = |id| {
+                node_map
+                    .get(&id)
+                    .copied()
+                    .ok_or_else(|| format!("edge refers to unknown node id {}", id))
+            };
+            let (source, target) = (get_index(gene.source)?, get_index(gene.target)?);
+
+            let mut edge_data = EdgeData::new(gene.weight, gene.innov_number);
+            edge_data.set_disabled(gene.disabled);
+            network.graph.add_edge(source, target, edge_data);
+        }
+
+        Ok(network)
     }
 }
 
@@ -419,9 +723,10 @@ impl Display for NetworkGraph {
         for node in self.graph.raw_nodes() {
             writeln!(
                 f,
-                "  - {:?} (id = {})",
+                "  - {:?} (id = {}, activation = {})",
                 node.weight.kind(),
-                node.weight.id()
+                node.weight.id(),
+                node.weight.activation()
             )?;
         }
         writeln!(f, "# Edges")?;
@@ -449,6 +754,14 @@ mod tests {
     use super::*;
     use float_cmp::approx_eq;
 
+    fn node(kind: NodeKind, id: usize) -> NodeData {
+        let activation = match kind {
+            NodeKind::Input | NodeKind::Bias => ActivationKind::Linear,
+            NodeKind::Output | NodeKind::Hidden => ActivationKind::Sigmoid,
+        };
+        NodeData::new(kind, id, activation)
+    }
+
     fn graph_eq<N, E, Ty, Ix>(
         a: &petgraph::Graph<N, E, Ty, Ix>,
         b: &petgraph::Graph<N, E, Ty, Ix>,
@@ -471,7 +784,12 @@ mod tests {
         let input_number = 2;
         let output_number = 2;
         let mut innov_record = InnovationRecord::new(input_number, output_number);
-        let network = NetworkGraph::new(input_number, output_number, &mut innov_record);
+        let network = NetworkGraph::new(
+            input_number,
+            output_number,
+            ActivationKind::Sigmoid,
+            &mut innov_record,
+        );
 
         let mut graph = DiGraph::<NodeData, EdgeData>::new();
         for (i, &kind) in [
@@ -484,7 +802,7 @@ mod tests {
         .iter()
         .enumerate()
         {
-            graph.add_node(NodeData::new(kind, i));
+            graph.add_node(node(kind, i));
         }
 
         for (i, &(a, b)) in [(0, 2), (0, 3), (1, 2), (1, 3)].iter().enumerate() {
@@ -500,8 +818,18 @@ mod tests {
         let output_number = 1;
         let mut innov_record = InnovationRecord::new(input_number, output_number);
 
-        let network1 = NetworkGraph::new(input_number, output_number, &mut innov_record);
-        let network2 = NetworkGraph::new(input_number, output_number, &mut innov_record);
+        let network1 = NetworkGraph::new(
+            input_number,
+            output_number,
+            ActivationKind::Sigmoid,
+            &mut innov_record,
+        );
+        let network2 = NetworkGraph::new(
+            input_number,
+            output_number,
+            ActivationKind::Sigmoid,
+            &mut innov_record,
+        );
 
         let mut graph = DiGraph::<NodeData, EdgeData>::new();
         for (i, &kind) in [
@@ -513,7 +841,7 @@ mod tests {
         .iter()
         .enumerate()
         {
-            graph.add_node(NodeData::new(kind, i));
+            graph.add_node(node(kind, i));
         }
 
         for (i, &(a, b)) in [(0, 2), (1, 2)].iter().enumerate() {
@@ -529,17 +857,22 @@ mod tests {
         let input_number = 5;
         let output_number = 1;
         let mut innov_record = InnovationRecord::new(input_number, output_number);
-        let mut network = NetworkGraph::new(input_number, output_number, &mut innov_record);
+        let mut network = NetworkGraph::new(
+            input_number,
+            output_number,
+            ActivationKind::Sigmoid,
+            &mut innov_record,
+        );
         let input_nodes = network.input_nodes_mut().collect::<Vec<_>>();
 
         assert_eq!(
             input_nodes,
             vec![
-                &NodeData::new(NodeKind::Input, 0),
-                &NodeData::new(NodeKind::Input, 1),
-                &NodeData::new(NodeKind::Input, 2),
-                &NodeData::new(NodeKind::Input, 3),
-                &NodeData::new(NodeKind::Input, 4),
+                &node(NodeKind::Input, 0),
+                &node(NodeKind::Input, 1),
+                &node(NodeKind::Input, 2),
+                &node(NodeKind::Input, 3),
+                &node(NodeKind::Input, 4),
             ]
         )
     }
@@ -549,8 +882,17 @@ mod tests {
         let input_number = 2;
         let output_number = 1;
         let mut innov_record = InnovationRecord::new(input_number, output_number);
-        let mut network = NetworkGraph::new(input_number, output_number, &mut innov_record);
-        network.add_node(EdgeIndex::new(0), &mut innov_record);
+        let mut network = NetworkGraph::new(
+            input_number,
+            output_number,
+            ActivationKind::Sigmoid,
+            &mut innov_record,
+        );
+        network.add_node(
+            EdgeIndex::new(0),
+            ActivationKind::Sigmoid,
+            &mut innov_record,
+        );
 
         let mut graph = DiGraph::<NodeData, EdgeData>::new();
         for (i, &kind) in [
@@ -563,7 +905,7 @@ mod tests {
         .iter()
         .enumerate()
         {
-            graph.add_node(NodeData::new(kind, i));
+            graph.add_node(node(kind, i));
         }
 
         for (i, &(a, b)) in [(0, 2), (1, 2), (0, 4), (4, 2)].iter().enumerate() {
@@ -573,13 +915,65 @@ mod tests {
         assert!(graph_eq(&network.graph, &graph));
     }
 
+    #[test]
+    fn same_split_should_produce_same_node_id() {
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let mut networks: Vec<NetworkGraph> = (0..3)
+            .map(|_| NetworkGraph::new(2, 1, ActivationKind::Sigmoid, &mut innov_record))
+            .collect();
+        let split_id = |network: &NetworkGraph, index: NodeIndex| network.node(index).id();
+
+        let a = networks[0].add_node(
+            EdgeIndex::new(0),
+            ActivationKind::Sigmoid,
+            &mut innov_record,
+        );
+        let b = networks[1].add_node(
+            EdgeIndex::new(0),
+            ActivationKind::Sigmoid,
+            &mut innov_record,
+        );
+        assert_eq!(split_id(&networks[0], a), 4);
+        assert_eq!(split_id(&networks[1], b), 4);
+        assert_eq!(
+            networks[0].edge(EdgeIndex::new(2)).innov_number(),
+            networks[1].edge(EdgeIndex::new(2)).innov_number()
+        );
+
+        // Splitting the same connection again within a genome needs another node
+        networks[0].edge_mut(EdgeIndex::new(0)).set_disabled(false);
+        let c = networks[0].add_node(
+            EdgeIndex::new(0),
+            ActivationKind::Sigmoid,
+            &mut innov_record,
+        );
+        assert_eq!(split_id(&networks[0], c), 5);
+
+        innov_record.clear_splits();
+        let d = networks[2].add_node(
+            EdgeIndex::new(0),
+            ActivationKind::Sigmoid,
+            &mut innov_record,
+        );
+        assert_eq!(split_id(&networks[2], d), 6);
+    }
+
     #[test]
     fn add_connection_should_connect_nodes() {
         let input_number = 2;
         let output_number = 1;
         let mut innov_record = InnovationRecord::new(input_number, output_number);
-        let mut network = NetworkGraph::new(input_number, output_number, &mut innov_record);
-        network.add_node(EdgeIndex::new(0), &mut innov_record);
+        let mut network = NetworkGraph::new(
+            input_number,
+            output_number,
+            ActivationKind::Sigmoid,
+            &mut innov_record,
+        );
+        network.add_node(
+            EdgeIndex::new(0),
+            ActivationKind::Sigmoid,
+            &mut innov_record,
+        );
 
         let result = network.add_connection(1.into(), 4.into(), 0.0, &mut innov_record);
 
@@ -596,7 +990,7 @@ mod tests {
         .iter()
         .enumerate()
         {
-            graph.add_node(NodeData::new(kind, i));
+            graph.add_node(node(kind, i));
         }
 
         for (i, &(a, b)) in [(0, 2), (1, 2), (0, 4), (4, 2), (1, 4)].iter().enumerate() {
@@ -611,11 +1005,29 @@ mod tests {
         let output_number = 1;
         let mut innov_record = InnovationRecord::new(input_number, output_number);
 
-        let mut network1 = NetworkGraph::new(input_number, output_number, &mut innov_record);
-        let mut network2 = NetworkGraph::new(input_number, output_number, &mut innov_record);
+        let mut network1 = NetworkGraph::new(
+            input_number,
+            output_number,
+            ActivationKind::Sigmoid,
+            &mut innov_record,
+        );
+        let mut network2 = NetworkGraph::new(
+            input_number,
+            output_number,
+            ActivationKind::Sigmoid,
+            &mut innov_record,
+        );
 
-        network1.add_node(EdgeIndex::new(0), &mut innov_record);
-        network2.add_node(EdgeIndex::new(1), &mut innov_record);
+        network1.add_node(
+            EdgeIndex::new(0),
+            ActivationKind::Sigmoid,
+            &mut innov_record,
+        );
+        network2.add_node(
+            EdgeIndex::new(1),
+            ActivationKind::Sigmoid,
+            &mut innov_record,
+        );
 
         // Edge weight is same in network1, 2 - so constant seeding is not needed here.
         let mut rng = rand::thread_rng();
@@ -631,7 +1043,7 @@ mod tests {
         .iter()
         .enumerate()
         {
-            graph.add_node(NodeData::new(kind, i));
+            graph.add_node(node(kind, i));
         }
 
         for (i, &(a, b)) in [(0, 2), (1, 2), (0, 4), (4, 2)].iter().enumerate() {
@@ -647,17 +1059,27 @@ mod tests {
         let output_number = 1;
         let mut innov_record = InnovationRecord::new(input_number, output_number);
 
-        let network1 = NetworkGraph::new(input_number, output_number, &mut innov_record);
-        let network2 = NetworkGraph::new(input_number, output_number, &mut innov_record);
+        let network1 = NetworkGraph::new(
+            input_number,
+            output_number,
+            ActivationKind::Sigmoid,
+            &mut innov_record,
+        );
+        let network2 = NetworkGraph::new(
+            input_number,
+            output_number,
+            ActivationKind::Sigmoid,
+            &mut innov_record,
+        );
 
         assert!(approx_eq!(
             f64,
-            network1.compatibility_metric(&network2, 1.0, 1.0),
+            network1.compatibility_metric(&network2, 1.0, 1.0, 0.0),
             0.0
         ));
         assert!(approx_eq!(
             f64,
-            network2.compatibility_metric(&network1, 1.0, 1.0),
+            network2.compatibility_metric(&network1, 1.0, 1.0, 0.0),
             0.0
         ));
     }
@@ -669,21 +1091,124 @@ mod tests {
 
         let mut innov_record = InnovationRecord::new(input_number, output_number);
 
-        let network1 = NetworkGraph::new(input_number, output_number, &mut innov_record);
-        let mut network2 = NetworkGraph::new(input_number, output_number, &mut innov_record);
+        let network1 = NetworkGraph::new(
+            input_number,
+            output_number,
+            ActivationKind::Sigmoid,
+            &mut innov_record,
+        );
+        let mut network2 = NetworkGraph::new(
+            input_number,
+            output_number,
+            ActivationKind::Sigmoid,
+            &mut innov_record,
+        );
 
-        network2.add_node(0.into(), &mut innov_record);
+        network2.add_node(0.into(), ActivationKind::Sigmoid, &mut innov_record);
         network2.edge_mut(1.into()).set_weight(2.0);
 
         assert!(approx_eq!(
             f64,
-            network1.compatibility_metric(&network2, 1.0, 2.0),
+            network1.compatibility_metric(&network2, 1.0, 2.0, 0.0),
             2.0 / 4.0 * 1.0 + 1.0 * 2.0
         ));
         assert!(approx_eq!(
             f64,
-            network2.compatibility_metric(&network1, 1.0, 2.0),
+            network2.compatibility_metric(&network1, 1.0, 2.0, 0.0),
             2.0 / 4.0 * 1.0 + 1.0 * 2.0
         ));
     }
+
+    #[test]
+    fn crossover_should_inherit_activation_of_either_parent() {
+        let input_number = 2;
+        let output_number = 1;
+        let mut innov_record = InnovationRecord::new(input_number, output_number);
+
+        let mut network1 = NetworkGraph::new(
+            input_number,
+            output_number,
+            ActivationKind::Sigmoid,
+            &mut innov_record,
+        );
+        network1.add_node(0.into(), ActivationKind::Tanh, &mut innov_record);
+        let mut network2 = network1.clone();
+        network2
+            .node_mut(4.into())
+            .set_activation(ActivationKind::Relu);
+
+        let mut rng = rand::thread_rng();
+        let activations: Vec<ActivationKind> = (0..100)
+            .map(|_| {
+                let offspring = network1.crossover(&network2, true, &mut rng).unwrap();
+                assert_eq!(offspring.hidden_node_count(), 1);
+                offspring.node(4.into()).activation()
+            })
+            .collect();
+
+        assert!(activations.contains(&ActivationKind::Tanh));
+        assert!(activations.contains(&ActivationKind::Relu));
+    }
+
+    #[test]
+    fn crossover_with_different_input_number_should_fail() {
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let network1 = NetworkGraph::new(2, 1, ActivationKind::Sigmoid, &mut innov_record);
+        let mut innov_record = InnovationRecord::new(3, 1);
+        let network2 = NetworkGraph::new(3, 1, ActivationKind::Sigmoid, &mut innov_record);
+
+        let result = network1.crossover(&network2, true, &mut rand::thread_rng());
+        assert!(matches!(result, Err(NeatError::IncompatibleCrossover(_))));
+    }
+
+    #[test]
+    fn compatibility_metric_should_count_activation_difference() {
+        let input_number = 2;
+        let output_number = 1;
+        let mut innov_record = InnovationRecord::new(input_number, output_number);
+
+        let mut network1 = NetworkGraph::new(
+            input_number,
+            output_number,
+            ActivationKind::Sigmoid,
+            &mut innov_record,
+        );
+        network1.add_node(0.into(), ActivationKind::Tanh, &mut innov_record);
+        let mut network2 = network1.clone();
+        network2
+            .node_mut(4.into())
+            .set_activation(ActivationKind::Relu);
+
+        assert!(approx_eq!(
+            f64,
+            network1.compatibility_metric(&network2, 1.0, 1.0, 0.0),
+            0.0
+        ));
+        assert!(approx_eq!(
+            f64,
+            network1.compatibility_metric(&network2, 1.0, 1.0, 3.0),
+            3.0
+        ));
+    }
+
+    #[test]
+    fn dot_should_rank_nodes_and_style_edges() {
+        let mut innov_record = InnovationRecord::new(1, 1);
+        let mut network = NetworkGraph::new(1, 1, ActivationKind::Sigmoid, &mut innov_record);
+        network.add_node(0.into(), ActivationKind::Tanh, &mut innov_record);
+        network.edge_mut(2.into()).set_weight(-2.0);
+
+        let dot = network.to_dot();
+        let expected = [
+            "        rank=source;\n        n0 [label=\"in 0\", shape=box];\n        n2 [label=\"bias\", shape=box];\n",
+            "        rank=sink;\n        n1 [label=\"out 1\\nSigmoid\", shape=doublecircle];\n",
+            "    n3 [label=\"3\\nTanh\"];\n",
+            "    n0 -> n1 [label=\"#0\", tooltip=\"1.0000\", color=\"#1f77b4\", penwidth=1.50, style=dashed];\n",
+            "    n3 -> n1 [label=\"#2\", tooltip=\"-2.0000\", color=\"#d62728\", penwidth=2.50];\n",
+        ];
+        for line in &expected {
+            assert!(dot.contains(line), "{} is missing in\n{}", line, dot);
+        }
+        assert!(dot.starts_with("digraph {\n") && dot.ends_with("}\n"));
+    }
 }
@@ -0,0 +1,258 @@
+use petgraph::graph::NodeIndex;
+use serde::{Deserialize, Serialize};
+
+use super::{network_graph::NetworkGraph, Network};
+use crate::{
+    activations::ActivationKind, error::NeatError, innovation_record::InnovationRecord,
+    node_kind::NodeKind,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recurrent {
+    fitness: Option<f64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    objectives: Vec<f64>,
+
+    // Number of propagation steps per activation
+    steps: usize,
+
+    // Output of each node from the last propagation step, indexed by node index
+    #[serde(skip)]
+    state: Vec<f64>,
+
+    // TOML requires tables to come after plain values
+    graph: NetworkGraph,
+}
+
+impl Recurrent {
+    // Forgets every node output memorized from previous activations
+    pub fn reset_state(&mut self) {
+        self.state.clear();
+        self.state.resize(self.graph.node_count(), 0.0);
+    }
+}
+
+impl Network for Recurrent {
+    fn new(
+        input_number: usize,
+        output_number: usize,
+        output_func: ActivationKind,
+        innov_record: &mut InnovationRecord,
+    ) -> Self {
+        Self::from_graph(NetworkGraph::new(
+            input_number,
+            output_number,
+            output_func,
+            innov_record,
+        ))
+    }
+
+    fn from_graph(graph: NetworkGraph) -> Self {
+        Self {
+            state: vec![0.0; graph.node_count()],
+            graph,
+            fitness: None,
+            objectives: Vec::new(),
+            steps: 1,
+        }
+    }
+
+    fn activate(&mut self, inputs: &[f64]) -> Result<Vec<f64>, NeatError> {
+        let input_number = self.graph.input_number();
+        let output_number = self.graph.output_number();
+        if inputs.len() != input_number {
+            return Err(NeatError::InputSizeMismatch {
+                expected: input_number,
+                actual: inputs.len(),
+            });
+        }
+
+        // Graph has been mutated since the last activation
+        if self.state.len() != self.graph.node_count() {
+            self.reset_state();
+        }
+
+        self.state[..input_number].copy_from_slice(inputs);
+        self.state[input_number + output_number] = 1.0;
+
+        for _ in 0..self.steps {
+            // Every connection carries the output of its source node from the previous step
+            for edge_index in 0..self.graph.edge_count() {
+                let (target, input) = {
+                    let edge = &self.graph.inner_data().raw_edges()[edge_index];
+                    if edge.weight.is_disabled() {
+                        continue;
+                    }
+                    (
+                        edge.target(),
+                        self.state[edge.source().index()] * edge.weight.get_weight(),
+                    )
+                };
+                self.graph.node_mut(target).add_input(input);
+            }
+
+            for (index, value) in self.state.iter_mut().enumerate() {
+                let node = self.graph.node(NodeIndex::new(index));
+                if node.kind() == NodeKind::Input || node.kind() == NodeKind::Bias {
+                    continue;
+                }
+
+                // Node without any incoming signal outputs zero
+                *value = node.activate().unwrap_or(0.0);
+            }
+
+            self.graph.clear_sum();
+        }
+
+        Ok(self.state[input_number..input_number + output_number].to_vec())
+    }
+
+    fn steps(&self) -> usize {
+        self.steps
+    }
+
+    fn set_steps(&mut self, steps: usize) {
+        self.steps = steps;
+    }
+
+    fn graph(&self) -> &NetworkGraph {
+        &self.graph
+    }
+
+    fn graph_mut(&mut self) -> &mut NetworkGraph {
+        &mut self.graph
+    }
+
+    fn mutate_add_connection(
+        &mut self,
+        source: NodeIndex,
+        target: NodeIndex,
+        weight: f64,
+        innov_record: &mut InnovationRecord,
+    ) -> bool {
+        // Cycles and self-loops are allowed
+        let target_kind = self.graph.node(target).kind();
+        if target_kind == NodeKind::Input
+            || target_kind == NodeKind::Bias
+            || self.graph.has_connection(source, target)
+        {
+            return false;
+        }
+
+        self.graph
+            .add_connection(source, target, weight, innov_record);
+        true
+    }
+
+    fn evaluate(&mut self, fitness: f64) {
+        // Non-finite fitness is kept as-is, so that the pool can reject it
+        self.fitness = Some(if fitness.is_finite() {
+            fitness.max(0.0)
+        } else {
+            fitness
+        });
+    }
+
+    fn fitness(&self) -> Option<f64> {
+        self.fitness
+    }
+
+    fn evaluate_objectives(&mut self, objectives: Vec<f64>) {
+        self.objectives = objectives;
+    }
+
+    fn objectives(&self) -> &[f64] {
+        &self.objectives
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ActivationKind as Kind;
+    use super::*;
+    use crate::activations::sigmoid;
+
+    #[test]
+    fn initial_network_activation_should_sum_input_and_squash() {
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let mut network = Recurrent::new(2, 1, Kind::Sigmoid, &mut innov_record);
+
+        assert_eq!(
+            network.activate(&[1.0, 2.0]).unwrap(),
+            vec![sigmoid(1.0 + 2.0)]
+        );
+        assert!(matches!(
+            network.activate(&[1.0]),
+            Err(NeatError::InputSizeMismatch {
+                expected: 2,
+                actual: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn self_loop_should_remember_previous_output() {
+        let mut innov_record = InnovationRecord::new(1, 1);
+        let mut network = Recurrent::new(1, 1, Kind::Linear, &mut innov_record);
+        assert!(network.mutate_add_connection(1.into(), 1.into(), 0.5, &mut innov_record));
+
+        assert_eq!(network.activate(&[1.0]).unwrap(), vec![1.0]);
+        assert_eq!(network.activate(&[1.0]).unwrap(), vec![1.5]);
+        assert_eq!(network.activate(&[0.0]).unwrap(), vec![0.75]);
+
+        network.reset_state();
+        assert_eq!(network.activate(&[0.0]).unwrap(), vec![0.0]);
+    }
+
+    #[test]
+    fn batch_activation_should_carry_state_between_rows() {
+        let mut innov_record = InnovationRecord::new(1, 1);
+        let mut network = Recurrent::new(1, 1, Kind::Linear, &mut innov_record);
+        assert!(network.mutate_add_connection(1.into(), 1.into(), 0.5, &mut innov_record));
+
+        assert_eq!(
+            network.activate_batch(&[&[1.0], &[1.0], &[0.0]]).unwrap(),
+            vec![vec![1.0], vec![1.5], vec![0.75]]
+        );
+    }
+
+    #[test]
+    fn hidden_node_should_delay_signal_by_one_step() {
+        let mut innov_record = InnovationRecord::new(1, 1);
+        let mut network = Recurrent::new(1, 1, Kind::Linear, &mut innov_record);
+        assert!(network.mutate_add_node(0.into(), Kind::Linear, &mut innov_record));
+
+        assert_eq!(network.activate(&[2.0]).unwrap(), vec![0.0]);
+        assert_eq!(network.activate(&[2.0]).unwrap(), vec![2.0]);
+
+        network.reset_state();
+        network.set_steps(2);
+        assert_eq!(network.activate(&[2.0]).unwrap(), vec![2.0]);
+    }
+
+    #[test]
+    fn offspring_should_inherit_steps() {
+        let mut innov_record = InnovationRecord::new(1, 1);
+        let mut a = Recurrent::new(1, 1, Kind::Linear, &mut innov_record);
+        let mut b = a.clone();
+        a.set_steps(3);
+        a.evaluate(1.0);
+        b.evaluate(0.5);
+
+        let offspring = a.crossover(&b, &mut rand::thread_rng()).unwrap();
+        assert_eq!(offspring.steps(), 3);
+    }
+
+    #[test]
+    fn mutate_add_connection_should_allow_cycle() {
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let mut network = Recurrent::new(2, 1, Kind::Sigmoid, &mut innov_record);
+        assert!(network.mutate_add_node(0.into(), Kind::Linear, &mut innov_record));
+        assert!(network.mutate_add_connection(2.into(), 4.into(), 1.0, &mut innov_record));
+        assert!(network.graph().has_cycle());
+
+        assert!(!network.mutate_add_connection(2.into(), 4.into(), 1.0, &mut innov_record));
+        assert!(!network.mutate_add_connection(4.into(), 0.into(), 1.0, &mut innov_record));
+        assert!(!network.mutate_add_connection(4.into(), 3.into(), 1.0, &mut innov_record));
+    }
+}
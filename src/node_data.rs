@@ -1,19 +1,33 @@
 use crate::activations::{activate, ActivationKind};
 use crate::node_kind::NodeKind;
 
-#[derive(Debug, PartialEq, Clone)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct NodeData {
     kind: NodeKind,
     id: usize,
+    // Genomes saved before nodes had their own activation load as sigmoid
+    #[serde(default = "default_activation")]
+    activation: ActivationKind,
+
+    // Activation state is transient and is not part of the genome
+    #[serde(skip)]
     input_sum: f64,
+    #[serde(skip)]
     activated: bool,
 }
 
+fn default_activation() -> ActivationKind {
+    ActivationKind::Sigmoid
+}
+
 impl NodeData {
-    pub fn new(kind: NodeKind, id: usize) -> Self {
+    pub fn new(kind: NodeKind, id: usize, activation: ActivationKind) -> Self {
         Self {
             kind,
             id,
+            activation,
             input_sum: 0.0,
             activated: false,
         }
@@ -29,11 +43,11 @@ impl NodeData {
         self.activated = false;
     }
 
-    pub fn activate(&self, func: ActivationKind) -> Option<f64> {
+    pub fn activate(&self) -> Option<f64> {
         if self.kind == NodeKind::Input || self.kind == NodeKind::Bias {
             Some(self.input_sum)
         } else if self.activated {
-            Some(activate(func, self.input_sum))
+            Some(activate(self.activation, self.input_sum))
         } else if self.kind == NodeKind::Output {
             Some(self.input_sum)
         } else {
@@ -48,4 +62,12 @@ impl NodeData {
     pub fn id(&self) -> usize {
         self.id
     }
+
+    pub fn activation(&self) -> ActivationKind {
+        self.activation
+    }
+
+    pub fn set_activation(&mut self, activation: ActivationKind) {
+        self.activation = activation;
+    }
 }
@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+
+fn distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - y).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+// Behaviors found novel in previous generations, which later behaviors are compared against
+// so that the search doesn't return to regions it has already explored
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct NoveltyArchive {
+    behaviors: Vec<Vec<f64>>,
+}
+
+impl NoveltyArchive {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn behaviors(&self) -> &[Vec<f64>] {
+        &self.behaviors
+    }
+
+    pub fn len(&self) -> usize {
+        self.behaviors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.behaviors.is_empty()
+    }
+
+    // Sparseness of each behavior of the population, which is its mean distance to the k
+    // nearest behaviors among the rest of the population and the archive
+    pub fn novelty(&self, population: &[Vec<f64>], k: usize) -> Vec<f64> {
+        let mut distances = Vec::with_capacity(population.len() + self.behaviors.len());
+
+        population
+            .iter()
+            .enumerate()
+            .map(|(i, behavior)| {
+                distances.clear();
+                distances.extend(
+                    population
+                        .iter()
+                        .enumerate()
+                        .filter(|&(j, _)| j != i)
+                        .map(|(_, other)| distance(behavior, other)),
+                );
+                distances.extend(self.behaviors.iter().map(|other| distance(behavior, other)));
+                distances.sort_by(|a, b| a.total_cmp(b));
+
+                let nearest = &distances[..k.min(distances.len())];
+                if nearest.is_empty() {
+                    0.0
+                } else {
+                    nearest.iter().sum::<f64>() / nearest.len() as f64
+                }
+            })
+            .collect()
+    }
+
+    // Archives behaviors whose novelty exceeds the threshold, dropping the oldest ones
+    // beyond the limit
+    pub fn extend(
+        &mut self,
+        population: &[Vec<f64>],
+        novelty: &[f64],
+        threshold: f64,
+        limit: Option<usize>,
+    ) {
+        for (behavior, &value) in population.iter().zip(novelty) {
+            if value > threshold {
+                self.behaviors.push(behavior.clone());
+            }
+        }
+
+        if let Some(limit) = limit {
+            let excess = self.behaviors.len().saturating_sub(limit);
+            self.behaviors.drain(..excess);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn novelty_should_be_mean_distance_to_nearest_neighbors() {
+        let population = vec![vec![0.0, 0.0], vec![3.0, 4.0], vec![6.0, 8.0]];
+        let mut archive = NoveltyArchive::new();
+
+        assert_eq!(archive.novelty(&population, 1), vec![5.0, 5.0, 5.0]);
+        assert_eq!(archive.novelty(&population, 2), vec![7.5, 5.0, 7.5]);
+
+        archive.extend(&[vec![0.0, 1.0]], &[2.0], 1.0, None);
+        assert_eq!(
+            archive.novelty(&population, 1),
+            vec![1.0, 18f64.sqrt(), 5.0]
+        );
+        assert_eq!(archive.novelty(&[vec![0.0, 0.0]], 5), vec![1.0]);
+    }
+
+    #[test]
+    fn archive_should_keep_novel_behaviors_within_limit() {
+        let mut archive = NoveltyArchive::new();
+        let population = vec![vec![0.0], vec![1.0], vec![2.0]];
+
+        archive.extend(&population, &[0.5, 2.0, 3.0], 1.0, Some(3));
+        assert_eq!(archive.behaviors(), &[vec![1.0], vec![2.0]]);
+
+        archive.extend(&population, &[1.5, 0.0, 1.5], 1.0, Some(3));
+        assert_eq!(archive.behaviors(), &[vec![2.0], vec![0.0], vec![2.0]]);
+    }
+}
@@ -1,22 +1,64 @@
-use serde::Deserialize;
+use std::path::Path;
 
-use crate::activations::ActivationKind;
+use serde::{Deserialize, Serialize};
+use toml::{value::Table, Value};
 
-#[derive(Deserialize, Clone)]
+use crate::{
+    activations::ActivationKind,
+    error::NeatError,
+    innovation_record::SplitScope,
+    serialization::{self, Format, SerializationError},
+};
+
+fn default_recurrent_steps() -> usize {
+    1
+}
+
+fn default_species_elitism() -> usize {
+    2
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct Parameters {
     pub input_number: usize,
     pub output_number: usize,
     pub population: usize,
 
+    // Seed of the random number generator; a random seed is used if absent
+    #[serde(default)]
+    pub seed: Option<u64>,
+
+    // Activation of newly added hidden nodes
     pub hidden_activation: ActivationKind,
     pub output_activation: ActivationKind,
 
+    // Propagation steps per activation of recurrent networks
+    #[serde(default = "default_recurrent_steps")]
+    pub recurrent_steps: usize,
+
     pub mutation: MutationParameters,
     pub speciation: SpeciationParameters,
     pub reproduction: ReproductionParameters,
+
+    // Hidden nodes splitting the same connection get the same id within this scope
+    #[serde(default)]
+    pub split_scope: SplitScope,
+
+    #[serde(default)]
+    pub parsimony: ParsimonyParameters,
+
+    // Alternates complexifying and simplifying phases; mutation rates are static if absent
+    #[serde(default)]
+    pub phased_search: Option<PhasedSearchParameters>,
+
+    // Required by Pool::evaluate_novelty
+    #[serde(default)]
+    pub novelty: Option<NoveltyParameters>,
 }
 
-#[derive(Deserialize, Clone, Copy)]
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct MutationParameters {
     pub weight_perturbation: f64,
     pub weight_assign: f64,
@@ -25,6 +67,12 @@ pub struct MutationParameters {
     pub toggle_connection: f64,
     pub add_node: f64,
     pub remove_node: f64,
+    #[serde(default)]
+    pub change_activation: f64,
+
+    // Activations a hidden node can change into
+    #[serde(default)]
+    pub activation_options: Vec<ActivationKind>,
 
     pub weight_min: f64,
     pub weight_max: f64,
@@ -33,18 +81,751 @@ pub struct MutationParameters {
     pub perturb_max: f64,
 }
 
-#[derive(Deserialize, Clone, Copy)]
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(deny_unknown_fields)]
 pub struct SpeciationParameters {
     pub c1: f64, // mismatch gene coefficient
     pub c2: f64, // weight difference cofficient
+    #[serde(default)]
+    pub c3: f64, // activation difference coefficient
 
     pub compatibility_threshold: f64,
     pub survival_rate: f64,
 
     pub elitism: usize,
+
+    // Species whose best fitness hasn't improved for this many generations are removed
+    #[serde(default)]
+    pub max_stagnation: Option<usize>,
+    // Number of best species protected from stagnation removal
+    #[serde(default = "default_species_elitism")]
+    pub species_elitism: usize,
+
+    // Adjusts compatibility_threshold every generation to keep the number of species near a
+    // target; the threshold is fixed if absent
+    #[serde(default)]
+    pub dynamic_threshold: Option<DynamicThresholdParameters>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+pub struct DynamicThresholdParameters {
+    pub target_species_count: usize,
+    pub step: f64,
+
+    pub threshold_min: f64,
+    pub threshold_max: f64,
 }
 
-#[derive(Deserialize, Clone, Copy)]
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(deny_unknown_fields)]
 pub struct ReproductionParameters {
     pub crossover_rate: f64,
 }
+
+// Pressure towards small genomes; every field is off by default
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ParsimonyParameters {
+    // Genomes of equal fitness are ranked by complexity, the simpler one first
+    pub tie_break: bool,
+
+    // Subtracted from fitness for every hidden node and enabled edge after evaluation
+    pub node_penalty: f64,
+    pub edge_penalty: f64,
+
+    // Mutations adding nodes or enabling connections are skipped for genomes at the limit,
+    // and crossover children beyond it are replaced by a member of the species
+    pub max_hidden_nodes: Option<usize>,
+    pub max_edges: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+pub struct PhasedSearchParameters {
+    // Simplification starts when mean complexity exceeds the floor by this much, where the
+    // floor is mean complexity at the end of the last simplification
+    pub complexity_threshold: f64,
+    // ... or when best fitness hasn't improved for this many generations
+    #[serde(default)]
+    pub fitness_plateau: Option<usize>,
+
+    // Complexification resumes when mean complexity hasn't decreased for this many
+    // generations
+    pub simplification_plateau: usize,
+
+    // Rates used while simplifying, in which nodes and connections are never added
+    pub remove_node: f64,
+    pub remove_connection: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+pub struct NoveltyParameters {
+    // Novelty of a behavior is its mean distance to this many nearest neighbors
+    pub k_nearest: usize,
+
+    // Behaviors more novel than the threshold are added to the archive
+    pub archive_threshold: f64,
+    // Oldest behaviors are dropped once the archive exceeds the limit
+    #[serde(default)]
+    pub archive_limit: Option<usize>,
+
+    // Weight of novelty blended with fitness for selection; 0 selects by fitness only and
+    // 1 by novelty only
+    pub novelty_weight: f64,
+}
+
+// Defaults follow the original NEAT paper where it gives a value
+impl Default for MutationParameters {
+    fn default() -> Self {
+        Self {
+            weight_perturbation: 0.8,
+            weight_assign: 0.1,
+            add_connection: 0.05,
+            remove_connection: 0.0,
+            toggle_connection: 0.0,
+            add_node: 0.03,
+            remove_node: 0.0,
+            change_activation: 0.0,
+            activation_options: Vec::new(),
+            weight_min: -10.0,
+            weight_max: 10.0,
+            perturb_min: -1.0,
+            perturb_max: 1.0,
+        }
+    }
+}
+
+impl Default for SpeciationParameters {
+    fn default() -> Self {
+        Self {
+            c1: 1.0,
+            c2: 0.4,
+            c3: 0.0,
+            compatibility_threshold: 3.0,
+            survival_rate: 0.2,
+            elitism: 1,
+            max_stagnation: Some(15),
+            species_elitism: default_species_elitism(),
+            dynamic_threshold: None,
+        }
+    }
+}
+
+// k_nearest follows the novelty search paper; archive_threshold depends on the scale of
+// behaviors and usually needs tuning
+impl Default for NoveltyParameters {
+    fn default() -> Self {
+        Self {
+            k_nearest: 15,
+            archive_threshold: 1.0,
+            archive_limit: None,
+            novelty_weight: 1.0,
+        }
+    }
+}
+
+impl Default for ReproductionParameters {
+    fn default() -> Self {
+        Self {
+            crossover_rate: 0.75,
+        }
+    }
+}
+
+impl Parameters {
+    pub fn builder(input_number: usize, output_number: usize) -> ParametersBuilder {
+        ParametersBuilder::new(input_number, output_number)
+    }
+
+    pub fn from_toml_str(s: &str) -> Result<Self, NeatError> {
+        ParametersLoader::new().toml_str(s)?.load()
+    }
+
+    pub fn from_json_str(s: &str) -> Result<Self, NeatError> {
+        ParametersLoader::new().json_str(s)?.load()
+    }
+
+    pub fn from_toml_file(path: impl AsRef<Path>) -> Result<Self, NeatError> {
+        Self::from_toml_str(&std::fs::read_to_string(path).map_err(SerializationError::from)?)
+    }
+
+    pub fn from_json_file(path: impl AsRef<Path>) -> Result<Self, NeatError> {
+        Self::from_json_str(&std::fs::read_to_string(path).map_err(SerializationError::from)?)
+    }
+
+    // Loads the file in the format guessed from the extension (.json or .toml)
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, NeatError> {
+        ParametersLoader::new().file(path)?.load()
+    }
+
+    // Checks every field, and reports all invalid ones at once
+    pub fn validate(&self) -> Result<(), NeatError> {
+        let mut errors = Vec::new();
+
+        for &(name, value) in &[
+            ("input_number", self.input_number),
+            ("output_number", self.output_number),
+            ("population", self.population),
+            ("recurrent_steps", self.recurrent_steps),
+        ] {
+            if value == 0 {
+                errors.push(format!("{} must be positive", name));
+            }
+        }
+
+        let mutation = &self.mutation;
+        let speciation = &self.speciation;
+        for &(name, value) in &[
+            ("mutation.weight_perturbation", mutation.weight_perturbation),
+            ("mutation.weight_assign", mutation.weight_assign),
+            ("mutation.add_connection", mutation.add_connection),
+            ("mutation.remove_connection", mutation.remove_connection),
+            ("mutation.toggle_connection", mutation.toggle_connection),
+            ("mutation.add_node", mutation.add_node),
+            ("mutation.remove_node", mutation.remove_node),
+            ("mutation.change_activation", mutation.change_activation),
+            (
+                "reproduction.crossover_rate",
+                self.reproduction.crossover_rate,
+            ),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                errors.push(format!("{} must be in [0, 1], got {}", name, value));
+            }
+        }
+
+        if mutation.change_activation > 0.0 && mutation.activation_options.is_empty() {
+            errors.push(
+                "mutation.activation_options must not be empty when change_activation is positive"
+                    .to_owned(),
+            );
+        }
+        for &(name, min, max) in &[
+            ("weight", mutation.weight_min, mutation.weight_max),
+            ("perturb", mutation.perturb_min, mutation.perturb_max),
+        ] {
+            if min >= max {
+                errors.push(format!(
+                    "mutation.{0}_min must be less than mutation.{0}_max, got {1} and {2}",
+                    name, min, max
+                ));
+            }
+        }
+
+        for &(name, value) in &[
+            ("speciation.c1", speciation.c1),
+            ("speciation.c2", speciation.c2),
+            ("speciation.c3", speciation.c3),
+            (
+                "speciation.compatibility_threshold",
+                speciation.compatibility_threshold,
+            ),
+        ] {
+            if value < 0.0 {
+                errors.push(format!("{} must not be negative, got {}", name, value));
+            }
+        }
+        if !(speciation.survival_rate > 0.0 && speciation.survival_rate <= 1.0) {
+            errors.push(format!(
+                "speciation.survival_rate must be in (0, 1], got {}",
+                speciation.survival_rate
+            ));
+        }
+
+        // Elites of every species are copied before reproduction, so they must fit in the
+        // population; species count is known in advance only with a dynamic threshold
+        let species_count = speciation
+            .dynamic_threshold
+            .map_or(1, |dynamic| dynamic.target_species_count);
+        if speciation.elitism * species_count > self.population {
+            errors.push(format!(
+                "speciation.elitism of {} for {} species exceeds population {}",
+                speciation.elitism, species_count, self.population
+            ));
+        }
+
+        if let Some(dynamic) = speciation.dynamic_threshold {
+            if dynamic.target_species_count == 0 {
+                errors.push(
+                    "speciation.dynamic_threshold.target_species_count must be positive".to_owned(),
+                );
+            }
+            if dynamic.step <= 0.0 {
+                errors.push(format!(
+                    "speciation.dynamic_threshold.step must be positive, got {}",
+                    dynamic.step
+                ));
+            }
+            if dynamic.threshold_min > dynamic.threshold_max {
+                errors.push(format!(
+                    "speciation.dynamic_threshold.threshold_min must not exceed threshold_max, \
+                     got {} and {}",
+                    dynamic.threshold_min, dynamic.threshold_max
+                ));
+            }
+        }
+
+        for &(name, value) in &[
+            ("parsimony.node_penalty", self.parsimony.node_penalty),
+            ("parsimony.edge_penalty", self.parsimony.edge_penalty),
+        ] {
+            if value < 0.0 {
+                errors.push(format!("{} must not be negative, got {}", name, value));
+            }
+        }
+
+        if let Some(phased) = self.phased_search {
+            if phased.complexity_threshold <= 0.0 {
+                errors.push(format!(
+                    "phased_search.complexity_threshold must be positive, got {}",
+                    phased.complexity_threshold
+                ));
+            }
+            if phased.simplification_plateau == 0 {
+                errors.push("phased_search.simplification_plateau must be positive".to_owned());
+            }
+            for &(name, value) in &[
+                ("phased_search.remove_node", phased.remove_node),
+                ("phased_search.remove_connection", phased.remove_connection),
+            ] {
+                if !(0.0..=1.0).contains(&value) {
+                    errors.push(format!("{} must be in [0, 1], got {}", name, value));
+                }
+            }
+        }
+
+        if let Some(novelty) = self.novelty {
+            if novelty.k_nearest == 0 {
+                errors.push("novelty.k_nearest must be positive".to_owned());
+            }
+            if novelty.archive_threshold < 0.0 {
+                errors.push(format!(
+                    "novelty.archive_threshold must not be negative, got {}",
+                    novelty.archive_threshold
+                ));
+            }
+            if !(0.0..=1.0).contains(&novelty.novelty_weight) {
+                errors.push(format!(
+                    "novelty.novelty_weight must be in [0, 1], got {}",
+                    novelty.novelty_weight
+                ));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(NeatError::InvalidParameters(errors))
+        }
+    }
+}
+
+// Builds parameters from layers, where each layer overrides fields set by previous ones.
+// Layers don't need to be complete, but the merged result must have every required field.
+//
+//     let params = ParametersLoader::new()
+//         .file("params/base.toml")?
+//         .file("params/experiment.toml")?
+//         .env("NEAT")?
+//         .load()?;
+#[derive(Default)]
+pub struct ParametersLoader {
+    table: Table,
+}
+
+impl ParametersLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn toml_str(self, s: &str) -> Result<Self, NeatError> {
+        let table = serialization::from_str(s, Format::Toml)?;
+        Ok(self.merge(table))
+    }
+
+    pub fn json_str(self, s: &str) -> Result<Self, NeatError> {
+        let mut value: serde_json::Value = serialization::from_str(s, Format::Json)?;
+        // TOML has no null, and absent fields are the same as null ones
+        remove_nulls(&mut value);
+
+        let table = serde_json::from_value(value).map_err(SerializationError::from)?;
+        Ok(self.merge(table))
+    }
+
+    // Loads the file in the format guessed from the extension (.json or .toml)
+    pub fn file(self, path: impl AsRef<Path>) -> Result<Self, NeatError> {
+        let path = path.as_ref();
+        let format = Format::from_path(path)
+            .ok_or_else(|| SerializationError::UnknownFormat(path.display().to_string()))?;
+        let s = std::fs::read_to_string(path).map_err(SerializationError::from)?;
+
+        match format {
+            Format::Json => self.json_str(&s),
+            Format::Toml => self.toml_str(&s),
+        }
+    }
+
+    // Overrides fields with environment variables named after the prefix and the field path
+    // separated by double underscores, e.g. NEAT_MUTATION__ADD_NODE=0.1 with prefix "NEAT".
+    // Unlike vars, variables that don't refer to a parameter or aren't valid UTF-8 are
+    // skipped, since the environment may hold unrelated ones with the same prefix.
+    pub fn env(self, prefix: &str) -> Result<Self, NeatError> {
+        let schema = serde_json::to_value(ParametersBuilder::new(1, 1).params)
+            .map_err(SerializationError::from)?;
+        let var_prefix = format!("{}_", prefix);
+
+        let vars = std::env::vars_os().filter_map(|(name, value)| {
+            let name = name.into_string().ok()?;
+            let path = name.strip_prefix(&var_prefix)?.to_lowercase();
+            let keys: Vec<&str> = path.split("__").collect();
+            if !is_parameter_path(&schema, &keys) {
+                return None;
+            }
+
+            Some((name, value.into_string().ok()?))
+        });
+        self.vars(prefix, vars)
+    }
+
+    // Same as env, but reads variables from the given pairs instead of the environment, and
+    // rejects the ones that don't refer to a parameter
+    pub fn vars(
+        mut self,
+        prefix: &str,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self, NeatError> {
+        let prefix = format!("{}_", prefix);
+        let mut errors = Vec::new();
+
+        for (name, raw_value) in vars {
+            let path = match name.strip_prefix(&prefix) {
+                Some(path) => path.to_lowercase(),
+                None => continue,
+            };
+
+            // Values that aren't valid TOML, like activation names, are taken as strings
+            let value = toml::from_str::<Table>(&format!("value = {}", raw_value))
+                .ok()
+                .and_then(|mut table| table.remove("value"))
+                .unwrap_or(Value::String(raw_value));
+
+            let keys: Vec<&str> = path.split("__").collect();
+            if !set_path(&mut self.table, &keys, value) {
+                errors.push(format!("{} doesn't refer to a parameter", name));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(self)
+        } else {
+            Err(NeatError::InvalidParameters(errors))
+        }
+    }
+
+    pub fn load(self) -> Result<Parameters, NeatError> {
+        let params =
+            Parameters::deserialize(Value::Table(self.table)).map_err(SerializationError::from)?;
+        params.validate()?;
+        Ok(params)
+    }
+
+    fn merge(mut self, table: Table) -> Self {
+        merge_table(&mut self.table, table);
+        self
+    }
+}
+
+// Whether the keys lead to a field of the serialized parameters. Paths into absent optional
+// sections, which serialize as null, can't be checked and are assumed to be valid.
+fn is_parameter_path(schema: &serde_json::Value, keys: &[&str]) -> bool {
+    match (schema, keys) {
+        (serde_json::Value::Null, _) => true,
+        (serde_json::Value::Object(map), [key, rest @ ..]) => map
+            .get(*key)
+            .is_some_and(|value| is_parameter_path(value, rest)),
+        (serde_json::Value::Object(_), []) => false,
+        (_, keys) => keys.is_empty(),
+    }
+}
+
+fn remove_nulls(value: &mut serde_json::Value) {
+    if let serde_json::Value::Object(map) = value {
+        let null_keys: Vec<String> = map
+            .iter()
+            .filter(|(_, v)| v.is_null())
+            .map(|(k, _)| k.clone())
+            .collect();
+        for key in null_keys {
+            map.remove(&key);
+        }
+        map.values_mut().for_each(remove_nulls);
+    }
+}
+
+fn merge_table(base: &mut Table, overlay: Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base_table)), Value::Table(overlay_table)) => {
+                merge_table(base_table, overlay_table)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+// Returns false if a key in the middle of the path is a plain value
+fn set_path(table: &mut Table, keys: &[&str], value: Value) -> bool {
+    match keys {
+        [] => false,
+        [key] => {
+            table.insert((*key).to_owned(), value);
+            true
+        }
+        [key, rest @ ..] => {
+            let entry = table
+                .entry((*key).to_owned())
+                .or_insert_with(|| Value::Table(Table::new()));
+            match entry {
+                Value::Table(inner) => set_path(inner, rest, value),
+                _ => false,
+            }
+        }
+    }
+}
+
+pub struct ParametersBuilder {
+    params: Parameters,
+}
+
+impl ParametersBuilder {
+    pub fn new(input_number: usize, output_number: usize) -> Self {
+        Self {
+            params: Parameters {
+                input_number,
+                output_number,
+                population: 150,
+                seed: None,
+                hidden_activation: ActivationKind::Sigmoid,
+                output_activation: ActivationKind::Sigmoid,
+                recurrent_steps: default_recurrent_steps(),
+                mutation: MutationParameters::default(),
+                speciation: SpeciationParameters::default(),
+                reproduction: ReproductionParameters::default(),
+                split_scope: SplitScope::default(),
+                parsimony: ParsimonyParameters::default(),
+                phased_search: None,
+                novelty: None,
+            },
+        }
+    }
+
+    pub fn population(mut self, population: usize) -> Self {
+        self.params.population = population;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.params.seed = Some(seed);
+        self
+    }
+
+    pub fn hidden_activation(mut self, activation: ActivationKind) -> Self {
+        self.params.hidden_activation = activation;
+        self
+    }
+
+    pub fn output_activation(mut self, activation: ActivationKind) -> Self {
+        self.params.output_activation = activation;
+        self
+    }
+
+    pub fn recurrent_steps(mut self, steps: usize) -> Self {
+        self.params.recurrent_steps = steps;
+        self
+    }
+
+    pub fn mutation(mut self, mutation: MutationParameters) -> Self {
+        self.params.mutation = mutation;
+        self
+    }
+
+    pub fn speciation(mut self, speciation: SpeciationParameters) -> Self {
+        self.params.speciation = speciation;
+        self
+    }
+
+    pub fn reproduction(mut self, reproduction: ReproductionParameters) -> Self {
+        self.params.reproduction = reproduction;
+        self
+    }
+
+    pub fn split_scope(mut self, split_scope: SplitScope) -> Self {
+        self.params.split_scope = split_scope;
+        self
+    }
+
+    pub fn parsimony(mut self, parsimony: ParsimonyParameters) -> Self {
+        self.params.parsimony = parsimony;
+        self
+    }
+
+    pub fn phased_search(mut self, phased_search: PhasedSearchParameters) -> Self {
+        self.params.phased_search = Some(phased_search);
+        self
+    }
+
+    pub fn novelty(mut self, novelty: NoveltyParameters) -> Self {
+        self.params.novelty = Some(novelty);
+        self
+    }
+
+    pub fn build(self) -> Result<Parameters, NeatError> {
+        self.params.validate()?;
+        Ok(self.params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serialization;
+
+    #[test]
+    fn builder_defaults_should_be_valid() {
+        let params = Parameters::builder(2, 1).seed(7).build().unwrap();
+
+        assert_eq!(params.population, 150);
+        assert_eq!(params.seed, Some(7));
+        assert_eq!(params.speciation.max_stagnation, Some(15));
+    }
+
+    #[test]
+    fn example_parameters_should_be_valid() {
+        for name in &["xor", "sin", "flappy"] {
+            let params: Parameters = serialization::load(format!("params/{}.toml", name)).unwrap();
+            assert!(params.validate().is_ok(), "params/{}.toml is invalid", name);
+        }
+    }
+
+    #[test]
+    fn validate_should_report_every_invalid_field() {
+        let result = Parameters::builder(2, 0)
+            .population(2)
+            .mutation(MutationParameters {
+                add_node: 1.5,
+                weight_min: 1.0,
+                weight_max: -1.0,
+                ..Default::default()
+            })
+            .speciation(SpeciationParameters {
+                survival_rate: 0.0,
+                elitism: 3,
+                ..Default::default()
+            })
+            .build();
+
+        let errors = match result {
+            Err(NeatError::InvalidParameters(errors)) => errors,
+            _ => panic!("parameters should be invalid"),
+        };
+        assert_eq!(errors.len(), 5);
+        assert!(errors[0].starts_with("output_number"));
+        assert!(errors[1].starts_with("mutation.add_node"));
+        assert!(errors[2].starts_with("mutation.weight_min"));
+        assert!(errors[3].starts_with("speciation.survival_rate"));
+        assert!(errors[4].starts_with("speciation.elitism"));
+    }
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|&(name, value)| (name.to_owned(), value.to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn layers_should_override_previous_ones() {
+        let experiment = indoc::indoc! {"
+            population = 200
+
+            [mutation]
+            add_connection = 0.3
+        "};
+
+        let params = ParametersLoader::new()
+            .file("params/xor.toml")
+            .unwrap()
+            .toml_str(experiment)
+            .unwrap()
+            .vars(
+                "NEAT",
+                vars(&[
+                    ("NEAT_MUTATION__ADD_NODE", "0.1"),
+                    ("NEAT_HIDDEN_ACTIVATION", "Tanh"),
+                    ("NEAT_SPECIATION__MAX_STAGNATION", "20"),
+                    ("OTHER_POPULATION", "1"),
+                ]),
+            )
+            .unwrap()
+            .load()
+            .unwrap();
+
+        assert_eq!(params.population, 200);
+        assert_eq!(params.mutation.add_connection, 0.3);
+        assert_eq!(params.mutation.add_node, 0.1);
+        assert_eq!(params.mutation.weight_perturbation, 0.8);
+        assert_eq!(params.hidden_activation, ActivationKind::Tanh);
+        assert_eq!(params.speciation.max_stagnation, Some(20));
+        assert_eq!(
+            params.speciation.species_elitism,
+            SpeciationParameters::default().species_elitism
+        );
+    }
+
+    #[test]
+    fn unrelated_environment_variables_should_be_skipped() {
+        std::env::set_var("NEATENVTEST_POPULATION", "42");
+        std::env::set_var("NEATENVTEST_SPECIATION__C3", "0.5");
+        std::env::set_var("NEATENVTEST_HOME", "/home/neat");
+        std::env::set_var("NEATENVTEST_MUTATION__ADD_NODE__RATE", "1");
+
+        let params = ParametersLoader::new()
+            .file("params/xor.toml")
+            .unwrap()
+            .env("NEATENVTEST")
+            .unwrap()
+            .load()
+            .unwrap();
+        assert_eq!(params.population, 42);
+        assert_eq!(params.speciation.c3, 0.5);
+    }
+
+    #[test]
+    fn unknown_override_should_be_rejected() {
+        let loader = ParametersLoader::new().file("params/xor.toml").unwrap();
+        let result = loader.vars("NEAT", vars(&[("NEAT_POPULATION__SIZE", "10")]));
+        assert!(matches!(result, Err(NeatError::InvalidParameters(_))));
+
+        let loader = ParametersLoader::new().file("params/xor.toml").unwrap();
+        let result = loader
+            .vars("NEAT", vars(&[("NEAT_MUTATION__ADD_NDOE", "0.1")]))
+            .unwrap()
+            .load();
+        assert!(matches!(result, Err(NeatError::Serialization(_))));
+    }
+
+    #[test]
+    fn parameters_should_roundtrip_through_json() {
+        let params = Parameters::from_toml_file("params/sin.toml").unwrap();
+        let json = serialization::to_string(&params, Format::Json).unwrap();
+        let deserialized = Parameters::from_json_str(&json).unwrap();
+
+        assert_eq!(deserialized.population, 300);
+        assert_eq!(deserialized.output_activation, ActivationKind::Linear);
+        assert_eq!(deserialized.mutation.perturb_min, -0.1);
+    }
+}
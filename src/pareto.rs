@@ -0,0 +1,150 @@
+// Pareto ranking of NSGA-II (Deb et al., 2002). Every objective is maximized.
+
+// Whether a is at least as good as b in every objective and better in one
+pub fn dominates(a: &[f64], b: &[f64]) -> bool {
+    let mut better = false;
+    for (x, y) in a.iter().zip(b) {
+        if x < y {
+            return false;
+        }
+        if x > y {
+            better = true;
+        }
+    }
+
+    better
+}
+
+// Index of the front each point belongs to, where front 0 is dominated by no point and
+// front n is dominated only by points of previous fronts
+pub fn pareto_ranks(objectives: &[&[f64]]) -> Vec<usize> {
+    let n = objectives.len();
+    let mut dominated: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut domination_count = vec![0; n];
+
+    for i in 0..n {
+        for j in (i + 1)..n {
+            if dominates(objectives[i], objectives[j]) {
+                dominated[i].push(j);
+                domination_count[j] += 1;
+            } else if dominates(objectives[j], objectives[i]) {
+                dominated[j].push(i);
+                domination_count[i] += 1;
+            }
+        }
+    }
+
+    let mut ranks = vec![0; n];
+    let mut front: Vec<usize> = (0..n).filter(|&i| domination_count[i] == 0).collect();
+    let mut rank = 0;
+    while !front.is_empty() {
+        let mut next_front = Vec::new();
+        for &i in &front {
+            ranks[i] = rank;
+            for &j in &dominated[i] {
+                domination_count[j] -= 1;
+                if domination_count[j] == 0 {
+                    next_front.push(j);
+                }
+            }
+        }
+
+        front = next_front;
+        rank += 1;
+    }
+
+    ranks
+}
+
+// Adds the distance between the neighbors of each point of the front along objective m
+fn add_crowding(distances: &mut [f64], front: &mut [usize], objectives: &[&[f64]], m: usize) {
+    let value = |i: usize| objectives[i][m];
+    front.sort_by(|&a, &b| value(a).total_cmp(&value(b)));
+
+    let (first, last) = (front[0], front[front.len() - 1]);
+    let range = value(last) - value(first);
+    distances[first] = f64::INFINITY;
+    distances[last] = f64::INFINITY;
+    if range <= 0.0 {
+        return;
+    }
+
+    for k in 1..front.len() - 1 {
+        distances[front[k]] += (value(front[k + 1]) - value(front[k - 1])) / range;
+    }
+}
+
+// Distance of each point to its neighbors in the same front, summed over objectives
+// normalized by their range in the front. Boundary points get infinite distance so that
+// the extremes of every front are kept.
+pub fn crowding_distances(objectives: &[&[f64]], ranks: &[usize]) -> Vec<f64> {
+    let mut distances = vec![0.0; objectives.len()];
+    let objective_count = objectives.first().map_or(0, |o| o.len());
+    let front_count = ranks.iter().max().map_or(0, |&max| max + 1);
+
+    for rank in 0..front_count {
+        let mut front: Vec<usize> = (0..ranks.len()).filter(|&i| ranks[i] == rank).collect();
+        for m in 0..objective_count {
+            add_crowding(&mut distances, &mut front, objectives, m);
+        }
+    }
+
+    distances
+}
+
+// Indices of the points from best to worst, ordered by front and then by crowding distance
+// in descending order
+pub fn nsga2_order(objectives: &[&[f64]]) -> Vec<usize> {
+    let ranks = pareto_ranks(objectives);
+    let distances = crowding_distances(objectives, &ranks);
+
+    let mut order: Vec<usize> = (0..objectives.len()).collect();
+    order.sort_by(|&a, &b| {
+        ranks[a]
+            .cmp(&ranks[b])
+            .then_with(|| distances[b].total_cmp(&distances[a]))
+    });
+
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn points_should_be_ranked_by_front() {
+        let points: Vec<&[f64]> = vec![
+            &[1.0, 5.0],
+            &[2.0, 2.0],
+            &[3.0, 3.0],
+            &[5.0, 1.0],
+            &[1.0, 1.0],
+        ];
+
+        assert!(dominates(points[2], points[1]));
+        assert!(!dominates(points[0], points[3]));
+        assert!(!dominates(points[1], points[1]));
+        assert_eq!(pareto_ranks(&points), vec![0, 1, 0, 0, 2]);
+    }
+
+    #[test]
+    fn crowded_points_should_come_last_in_their_front() {
+        let points: Vec<&[f64]> = vec![
+            &[0.0, 4.0],
+            &[1.0, 3.0],
+            &[1.2, 2.8],
+            &[3.0, 1.0],
+            &[4.0, 0.0],
+            &[0.0, 0.0],
+        ];
+        let ranks = pareto_ranks(&points);
+        let distances = crowding_distances(&points, &ranks);
+
+        assert_eq!(distances[0], f64::INFINITY);
+        assert_eq!(distances[4], f64::INFINITY);
+        assert_eq!(distances[5], f64::INFINITY);
+        assert!(distances[3] > distances[1] && distances[3] > distances[2]);
+        assert_eq!(nsga2_order(&points), [0, 4, 3, 2, 1, 5]);
+    }
+}
@@ -0,0 +1,141 @@
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Serialize};
+
+use crate::parameters::PhasedSearchParameters;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+pub enum SearchPhase {
+    // Structural mutations add nodes and connections, as in plain NEAT
+    #[default]
+    Complexifying,
+
+    // Structural mutations only remove nodes and connections
+    Simplifying,
+}
+
+impl Display for SearchPhase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SearchPhase::Complexifying => write!(f, "complexifying"),
+            SearchPhase::Simplifying => write!(f, "simplifying"),
+        }
+    }
+}
+
+// Phased search alternates between the phases (Green, 2004). Simplification starts when
+// mean complexity grows past the floor by a threshold, or when best fitness plateaus, and
+// ends when mean complexity stops decreasing. The complexity at the end of simplification
+// becomes the new floor.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct PhaseState {
+    phase: SearchPhase,
+    complexity_floor: Option<f64>,
+
+    // Lowest mean complexity of the current simplification phase
+    min_complexity: f64,
+    // Generations since mean complexity last decreased while simplifying
+    complexity_stale: usize,
+
+    best_fitness: Option<f64>,
+    fitness_stale: usize,
+}
+
+impl PhaseState {
+    pub fn phase(&self) -> SearchPhase {
+        self.phase
+    }
+
+    // Records an evaluated generation and returns the phase its offspring are mutated in
+    pub fn update(
+        &mut self,
+        params: &PhasedSearchParameters,
+        mean_complexity: f64,
+        best_fitness: f64,
+    ) -> SearchPhase {
+        let floor = *self.complexity_floor.get_or_insert(mean_complexity);
+
+        if self.best_fitness.is_none_or(|best| best_fitness > best) {
+            self.best_fitness = Some(best_fitness);
+            self.fitness_stale = 0;
+        } else {
+            self.fitness_stale += 1;
+        }
+
+        match self.phase {
+            SearchPhase::Complexifying => {
+                let plateau = params
+                    .fitness_plateau
+                    .is_some_and(|generations| self.fitness_stale >= generations);
+                if mean_complexity > floor + params.complexity_threshold || plateau {
+                    self.phase = SearchPhase::Simplifying;
+                    self.min_complexity = mean_complexity;
+                    self.complexity_stale = 0;
+                }
+            }
+            SearchPhase::Simplifying => {
+                if mean_complexity < self.min_complexity {
+                    self.min_complexity = mean_complexity;
+                    self.complexity_stale = 0;
+                } else {
+                    self.complexity_stale += 1;
+                }
+
+                if self.complexity_stale >= params.simplification_plateau {
+                    self.phase = SearchPhase::Complexifying;
+                    self.complexity_floor = Some(mean_complexity);
+                    self.fitness_stale = 0;
+                }
+            }
+        }
+
+        self.phase
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> PhasedSearchParameters {
+        PhasedSearchParameters {
+            complexity_threshold: 5.0,
+            fitness_plateau: None,
+            simplification_plateau: 2,
+            remove_node: 0.1,
+            remove_connection: 0.1,
+        }
+    }
+
+    #[test]
+    fn phase_should_switch_on_complexity_threshold_and_plateau() {
+        let params = params();
+        let mut state = PhaseState::default();
+
+        assert_eq!(state.update(&params, 4.0, 1.0), SearchPhase::Complexifying);
+        assert_eq!(state.update(&params, 9.0, 1.0), SearchPhase::Complexifying);
+        assert_eq!(state.update(&params, 9.5, 1.0), SearchPhase::Simplifying);
+
+        assert_eq!(state.update(&params, 7.0, 1.0), SearchPhase::Simplifying);
+        assert_eq!(state.update(&params, 7.0, 1.0), SearchPhase::Simplifying);
+        assert_eq!(state.update(&params, 7.5, 1.0), SearchPhase::Complexifying);
+
+        // Floor is now 7.5
+        assert_eq!(state.update(&params, 12.0, 1.0), SearchPhase::Complexifying);
+        assert_eq!(state.update(&params, 12.6, 1.0), SearchPhase::Simplifying);
+    }
+
+    #[test]
+    fn fitness_plateau_should_start_simplification() {
+        let params = PhasedSearchParameters {
+            fitness_plateau: Some(2),
+            ..params()
+        };
+        let mut state = PhaseState::default();
+
+        assert_eq!(state.update(&params, 4.0, 1.0), SearchPhase::Complexifying);
+        assert_eq!(state.update(&params, 4.0, 2.0), SearchPhase::Complexifying);
+        assert_eq!(state.update(&params, 4.0, 2.0), SearchPhase::Complexifying);
+        assert_eq!(state.update(&params, 4.0, 1.5), SearchPhase::Simplifying);
+    }
+}
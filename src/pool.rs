@@ -1,42 +1,223 @@
-use indoc::indoc;
 use rand::{
     distributions::{Distribution, Open01, Uniform},
-    RngCore,
+    seq::SliceRandom,
+    RngCore, SeedableRng,
 };
+use rand_chacha::ChaCha8Rng;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::{
-    innovation_record::InnovationRecord,
+    error::NeatError,
+    hyperneat::{Substrate, SubstrateNetwork},
+    innovation_record::{InnovationRecord, SplitScope},
     network::Network,
-    parameters::Parameters,
+    novelty::NoveltyArchive,
+    parameters::{Parameters, ParsimonyParameters},
+    pareto,
+    phase::{PhaseState, SearchPhase},
+    reporter::{ExtinctionReason, Reporter, StdoutReporter},
+    serialization::{self, Format, SerializationError},
     species::{Species, SpeciesInfo},
+    stats::{GenerationStats, SpeciesStats},
+    termination::{RunSummary, StopCondition},
 };
-use std::fmt::Debug;
+use std::{cmp::Ordering, fmt::Debug, path::Path, time::Instant};
 
 fn random01(rng: &mut impl RngCore) -> f64 {
     Open01.sample(rng)
 }
 
+// Orders genomes from the best to the worst
+fn best_first<T: Network>(a: &T, b: &T, parsimony: &ParsimonyParameters) -> Ordering {
+    if parsimony.tie_break {
+        b.compare_parsimonious(a).unwrap()
+    } else {
+        b.compare(a).unwrap()
+    }
+}
+
+// Genomes are sorted and allocated offspring by fitness, which must be a finite number
+fn check_fitness<T: Network>(index: usize, network: &T) -> Result<(), NeatError> {
+    match network.fitness() {
+        Some(fitness) if fitness.is_finite() => Ok(()),
+        Some(fitness) => Err(NeatError::InvalidFitness(format!(
+            "genome {} has fitness {}",
+            index, fitness
+        ))),
+        None => Err(NeatError::InvalidFitness(format!(
+            "genome {} hasn't been evaluated",
+            index
+        ))),
+    }
+}
+
+// Number of offspring of each species, proportional to its adjusted fitness. Offspring are
+// split evenly if every species has zero adjusted fitness.
+fn allocate_offspring(
+    adj_fitness_list: &[f64],
+    target_count: usize,
+) -> Result<Vec<usize>, NeatError> {
+    let adj_fitness_sum: f64 = adj_fitness_list.iter().sum();
+    if !adj_fitness_sum.is_finite() || adj_fitness_sum < 0.0 {
+        return Err(NeatError::InvalidFitness(format!(
+            "sum of adjusted fitness is {}",
+            adj_fitness_sum
+        )));
+    }
+
+    let species_count = adj_fitness_list.len();
+    let mut count_list: Vec<usize> = adj_fitness_list
+        .iter()
+        .map(|f| {
+            let share = if adj_fitness_sum > 0.0 {
+                f / adj_fitness_sum
+            } else {
+                1.0 / species_count as f64
+            };
+            (target_count as f64 * share).ceil() as usize
+        })
+        .collect();
+
+    // Rounding up gives at least the target count, so the excess is taken back
+    let total_count: usize = count_list.iter().sum();
+    let mut i = 0;
+    for _ in target_count..total_count {
+        while count_list[i % species_count] == 0 {
+            i += 1;
+        }
+        count_list[i % species_count] -= 1;
+        i += 1;
+    }
+
+    Ok(count_list)
+}
+
+fn penalize<T: Network>(network: &mut T, parsimony: &ParsimonyParameters) {
+    let graph = network.graph();
+    let penalty = parsimony.node_penalty * graph.hidden_node_count() as f64
+        + parsimony.edge_penalty * graph.enabled_edge_count() as f64;
+
+    if penalty > 0.0 {
+        network.evaluate(network.fitness().unwrap() - penalty);
+    }
+}
+
+fn exceeds_size_cap<T: Network>(network: &T, parsimony: &ParsimonyParameters) -> bool {
+    let graph = network.graph();
+    parsimony
+        .max_hidden_nodes
+        .is_some_and(|max| graph.hidden_node_count() > max)
+        || parsimony
+            .max_edges
+            .is_some_and(|max| graph.enabled_edge_count() > max)
+}
+
+// Checkpoints hold the 128-bit RNG position, which TOML can't represent
+fn checkpoint_path(path: &Path) -> Result<&Path, SerializationError> {
+    match Format::from_path(path) {
+        Some(Format::Json) => Ok(path),
+        _ => Err(SerializationError::UnsupportedFormat(
+            path.display().to_string(),
+        )),
+    }
+}
+
 pub struct Pool<T: Network + Debug + Clone> {
     list: Vec<T>,
     params: Parameters,
-    verbosity: usize,
+    compatibility_threshold: f64,
+    reporters: Vec<Box<dyn Reporter<T>>>,
     prev_species_info: Vec<SpeciesInfo<T>>,
     generation: usize,
+    rng: ChaCha8Rng,
+    stats: Option<GenerationStats>,
+
+    // Best fitness among every generation so far
+    best_fitness: Option<f64>,
+
+    novelty_archive: NoveltyArchive,
+    // Scores that replace fitness for selection in the next evolve, aligned with the list
+    selection_scores: Option<Vec<f64>>,
+    // Whether survival inside species is decided by Pareto ranking of objectives
+    multi_objective: bool,
+
+    phase_state: PhaseState,
+}
+
+// Everything needed to continue an evolution run exactly where it stopped
+#[derive(Serialize, Deserialize)]
+pub struct Checkpoint<T: Network + Debug + Clone> {
+    generation: usize,
+    params: Parameters,
+    compatibility_threshold: f64,
+    innov_record: InnovationRecord,
+    rng: ChaCha8Rng,
+    list: Vec<T>,
+    prev_species_info: Vec<SpeciesInfo<T>>,
+    #[serde(default)]
+    best_fitness: Option<f64>,
+    #[serde(default)]
+    novelty_archive: NoveltyArchive,
+    #[serde(default)]
+    phase_state: PhaseState,
+
+    // Results of an evaluation that the next evolve selects by
+    #[serde(default)]
+    stats: Option<GenerationStats>,
+    #[serde(default)]
+    selection_scores: Option<Vec<f64>>,
+    #[serde(default)]
+    multi_objective: bool,
+}
+
+impl<T: Network + Debug + Clone> Checkpoint<T> {
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
 }
 
-impl<'a, T: Network + Debug + Clone> Pool<T> {
+impl<T: Network + Debug + Clone> Pool<T> {
     pub fn new(params: Parameters, verbosity: usize, innov_record: &mut InnovationRecord) -> Self {
+        let rng = match params.seed {
+            Some(seed) => ChaCha8Rng::seed_from_u64(seed),
+            None => ChaCha8Rng::from_entropy(),
+        };
+        Self::with_rng(params, verbosity, innov_record, rng)
+    }
+
+    // Same seed and same fitness function always produce identical generations
+    pub fn with_seed(
+        params: Parameters,
+        verbosity: usize,
+        seed: u64,
+        innov_record: &mut InnovationRecord,
+    ) -> Self {
+        Self::with_rng(
+            params,
+            verbosity,
+            innov_record,
+            ChaCha8Rng::seed_from_u64(seed),
+        )
+    }
+
+    fn with_rng(
+        params: Parameters,
+        verbosity: usize,
+        innov_record: &mut InnovationRecord,
+        mut rng: ChaCha8Rng,
+    ) -> Self {
         let mut list: Vec<T> = Vec::new();
 
-        let mut rng = &mut rand::thread_rng();
         for _ in 0..params.population {
             let mut network = T::new(
                 params.input_number,
                 params.output_number,
-                params.hidden_activation,
                 params.output_activation,
                 innov_record,
             );
+            network.set_steps(params.recurrent_steps);
             network.graph_mut().randomize_weights(
                 params.mutation.weight_min,
                 params.mutation.weight_max,
@@ -47,13 +228,93 @@ impl<'a, T: Network + Debug + Clone> Pool<T> {
 
         Self {
             list,
+            compatibility_threshold: params.speciation.compatibility_threshold,
             params,
-            verbosity,
+            reporters: vec![Box::new(StdoutReporter::new(verbosity))],
             prev_species_info: Vec::new(),
             generation: 1,
+            rng,
+            stats: None,
+            best_fitness: None,
+            novelty_archive: NoveltyArchive::new(),
+            selection_scores: None,
+            multi_objective: false,
+            phase_state: PhaseState::default(),
         }
     }
 
+    pub fn from_checkpoint(
+        checkpoint: Checkpoint<T>,
+        verbosity: usize,
+    ) -> (Self, InnovationRecord) {
+        let pool = Self {
+            list: checkpoint.list,
+            params: checkpoint.params,
+            compatibility_threshold: checkpoint.compatibility_threshold,
+            reporters: vec![Box::new(StdoutReporter::new(verbosity))],
+            prev_species_info: checkpoint.prev_species_info,
+            generation: checkpoint.generation,
+            rng: checkpoint.rng,
+            stats: checkpoint.stats,
+            best_fitness: checkpoint.best_fitness,
+            novelty_archive: checkpoint.novelty_archive,
+            selection_scores: checkpoint.selection_scores,
+            multi_objective: checkpoint.multi_objective,
+            phase_state: checkpoint.phase_state,
+        };
+
+        (pool, checkpoint.innov_record)
+    }
+
+    // Loads a checkpoint saved by save_checkpoint and continues the run from it
+    pub fn resume_from(
+        path: impl AsRef<Path>,
+        verbosity: usize,
+    ) -> Result<(Self, InnovationRecord), NeatError>
+    where
+        T: DeserializeOwned,
+    {
+        let checkpoint: Checkpoint<T> = serialization::load(checkpoint_path(path.as_ref())?)?;
+        Ok(Self::from_checkpoint(checkpoint, verbosity))
+    }
+
+    pub fn checkpoint(&self, innov_record: &InnovationRecord) -> Checkpoint<T> {
+        Checkpoint {
+            generation: self.generation,
+            params: self.params.clone(),
+            compatibility_threshold: self.compatibility_threshold,
+            innov_record: innov_record.clone(),
+            rng: self.rng.clone(),
+            list: self.list.clone(),
+            prev_species_info: self.prev_species_info.clone(),
+            best_fitness: self.best_fitness,
+            novelty_archive: self.novelty_archive.clone(),
+            phase_state: self.phase_state.clone(),
+            stats: self.stats.clone(),
+            selection_scores: self.selection_scores.clone(),
+            multi_objective: self.multi_objective,
+        }
+    }
+
+    // Only .json paths are accepted, see checkpoint_path
+    pub fn save_checkpoint(
+        &self,
+        path: impl AsRef<Path>,
+        innov_record: &InnovationRecord,
+    ) -> Result<(), NeatError>
+    where
+        T: Serialize,
+    {
+        let path = checkpoint_path(path.as_ref())?;
+        Ok(serialization::save(&self.checkpoint(innov_record), path)?)
+    }
+
+    // Reporters are notified in the order they are added, after the built-in one printing
+    // according to verbosity
+    pub fn add_reporter(&mut self, reporter: impl Reporter<T> + 'static) {
+        self.reporters.push(Box::new(reporter));
+    }
+
     fn mutate(&self, network: &mut T, innov_record: &mut InnovationRecord, rng: &mut impl RngCore) {
         let delta_uniform = Uniform::new(
             self.params.mutation.perturb_min,
@@ -81,25 +342,57 @@ impl<'a, T: Network + Debug + Clone> Pool<T> {
             }
         }
 
-        if random01(rng) < self.params.mutation.add_node {
+        // Adding a node also adds an enabled edge, as the split edge is replaced by two, and
+        // two of them if the split edge was disabled
+        let parsimony = &self.params.parsimony;
+        let nodes_at_limit = |network: &T| {
+            parsimony
+                .max_hidden_nodes
+                .is_some_and(|max| network.graph().hidden_node_count() >= max)
+        };
+        let edges_beyond_limit = |network: &T, added: usize| {
+            parsimony
+                .max_edges
+                .is_some_and(|max| network.graph().enabled_edge_count() + added > max)
+        };
+        let edges_at_limit = |network: &T| edges_beyond_limit(network, 1);
+
+        // Structural mutations only remove while simplifying
+        let mutation = &self.params.mutation;
+        let (add_node, remove_node, add_connection, remove_connection) =
+            match (self.phase_state.phase(), &self.params.phased_search) {
+                (SearchPhase::Simplifying, Some(phased)) => {
+                    (0.0, phased.remove_node, 0.0, phased.remove_connection)
+                }
+                _ => (
+                    mutation.add_node,
+                    mutation.remove_node,
+                    mutation.add_connection,
+                    mutation.remove_connection,
+                ),
+            };
+
+        if random01(rng) < add_node && !nodes_at_limit(network) && !edges_at_limit(network) {
             if let Some(to_add) = network.graph().random_edge(rng) {
-                network.mutate_add_node(to_add, innov_record);
+                if !network.graph().edge(to_add).is_disabled() || !edges_beyond_limit(network, 2) {
+                    network.mutate_add_node(to_add, self.params.hidden_activation, innov_record);
+                }
             }
         }
 
-        if random01(rng) < self.params.mutation.remove_node {
+        if random01(rng) < remove_node {
             let to_remove = network.graph().random_node(rng);
             network.mutate_remove_node(to_remove, innov_record);
         }
 
-        if random01(rng) < self.params.mutation.add_connection {
+        if random01(rng) < add_connection && !edges_at_limit(network) {
             let source = network.graph().random_node(rng);
             let target = network.graph().random_node(rng);
 
             network.mutate_add_connection(source, target, assign_uniform.sample(rng), innov_record);
         }
 
-        if random01(rng) < self.params.mutation.remove_connection {
+        if random01(rng) < remove_connection {
             if let Some(to_remove) = network.graph().random_edge(rng) {
                 network.mutate_remove_connection(to_remove);
             }
@@ -107,13 +400,24 @@ impl<'a, T: Network + Debug + Clone> Pool<T> {
 
         if random01(rng) < self.params.mutation.toggle_connection {
             if let Some(to_toggle) = network.graph().random_edge(rng) {
-                network.mutate_toggle_connection(to_toggle);
+                // Enabling an edge counts toward the cap as much as adding one
+                if !network.graph().edge(to_toggle).is_disabled() || !edges_at_limit(network) {
+                    network.mutate_toggle_connection(to_toggle);
+                }
+            }
+        }
+
+        if random01(rng) < self.params.mutation.change_activation {
+            if let Some(to_change) = network.graph().random_hidden_node(rng) {
+                if let Some(&activation) = self.params.mutation.activation_options.choose(rng) {
+                    network.mutate_activation(to_change, activation);
+                }
             }
         }
     }
 
-    fn speciate(&'a self, innov_record: &mut InnovationRecord) -> Vec<Species<T>> {
-        // assumes genomes are sorted by fitness
+    fn speciate(&self, scores: &[f64], innov_record: &mut InnovationRecord) -> Vec<Species<'_, T>> {
+        // assumes genomes are sorted by score
         let mut new_species_set: Vec<Species<T>> = Vec::new();
 
         for mut species_info in self.prev_species_info.clone() {
@@ -121,15 +425,17 @@ impl<'a, T: Network + Debug + Clone> Pool<T> {
             new_species_set.push(Species::new(species_info));
         }
 
-        for network in &self.list {
+        for (network, &score) in self.list.iter().zip(scores) {
             let mut found = false;
 
             for species in &mut new_species_set {
                 if species.try_assign(
                     network,
+                    score,
                     self.params.speciation.c1,
                     self.params.speciation.c2,
-                    self.params.speciation.compatibility_threshold,
+                    self.params.speciation.c3,
+                    self.compatibility_threshold,
                 ) {
                     found = true;
                     break;
@@ -139,7 +445,7 @@ impl<'a, T: Network + Debug + Clone> Pool<T> {
             if !found {
                 let id = innov_record.new_species();
                 let mut new_species = Species::new(SpeciesInfo::new(id, network.clone(), 0));
-                new_species.force_assign(network);
+                new_species.force_assign(network, score);
                 new_species_set.push(new_species);
             }
         }
@@ -147,106 +453,471 @@ impl<'a, T: Network + Debug + Clone> Pool<T> {
         new_species_set
     }
 
-    fn log(&self, verbosity: usize, message: &str) {
-        if verbosity <= self.verbosity {
-            println!("{}", message);
+    fn adjust_compatibility_threshold(&mut self, species_count: usize) {
+        let dynamic = match self.params.speciation.dynamic_threshold {
+            Some(dynamic) => dynamic,
+            None => return,
+        };
+
+        // Lower threshold splits genomes into more species
+        if species_count < dynamic.target_species_count {
+            self.compatibility_threshold -= dynamic.step;
+        } else if species_count > dynamic.target_species_count {
+            self.compatibility_threshold += dynamic.step;
+        }
+        self.compatibility_threshold = self
+            .compatibility_threshold
+            .max(dynamic.threshold_min)
+            .min(dynamic.threshold_max);
+    }
+
+    // Removes species whose best fitness hasn't improved for max_stagnation generations,
+    // except the species_elitism species with the highest adjusted fitness. Returns id and
+    // stagnation of removed species.
+    fn remove_stagnant_species(
+        &self,
+        species_set: &mut Vec<Species<T>>,
+        adj_fitness_list: &mut Vec<f64>,
+    ) -> Vec<(usize, usize)> {
+        let max_stagnation = match self.params.speciation.max_stagnation {
+            Some(max_stagnation) => max_stagnation,
+            None => return Vec::new(),
+        };
+
+        let mut ranking: Vec<usize> = (0..species_set.len()).collect();
+        ranking.sort_by(|&a, &b| {
+            adj_fitness_list[b]
+                .partial_cmp(&adj_fitness_list[a])
+                .unwrap()
+        });
+
+        let mut keep: Vec<bool> = species_set
+            .iter()
+            .map(|s| s.stagnant() < max_stagnation)
+            .collect();
+        for &index in ranking.iter().take(self.params.speciation.species_elitism) {
+            keep[index] = true;
+        }
+
+        let removed = species_set
+            .iter()
+            .zip(&keep)
+            .filter(|(_, &k)| !k)
+            .map(|(species, _)| (species.id(), species.stagnant()))
+            .collect();
+
+        let mut flags = keep.iter();
+        species_set.retain(|_| *flags.next().unwrap());
+        let mut flags = keep.iter();
+        adj_fitness_list.retain(|_| *flags.next().unwrap());
+
+        removed
+    }
+
+    pub fn activate_nth(&mut self, index: usize, inputs: &[f64]) -> Result<Vec<f64>, NeatError> {
+        self.list[index].activate(inputs)
+    }
+
+    fn start_generation(&mut self) {
+        self.selection_scores = None;
+        self.multi_objective = false;
+        for reporter in &mut self.reporters {
+            reporter.on_generation_start(self.generation);
         }
     }
 
-    fn list_stats(&self, list: &[f64]) -> (f64, f64) {
-        let mut sum = 0.0;
-        for element in list {
-            sum += element;
+    pub fn evaluate<F: Fn(usize, &mut T)>(&mut self, evaluate: F) -> Result<&T, NeatError> {
+        self.start_generation();
+        let parsimony = self.params.parsimony;
+        for (i, network) in self.list.iter_mut().enumerate() {
+            evaluate(i, network);
+            check_fitness(i, network)?;
+            penalize(network, &parsimony);
         }
-        let mean = sum / list.len() as f64;
 
-        let mut delta_sum = 0.0;
-        for element in list {
-            delta_sum += (mean - element).powf(2.0) as f64;
+        self.finish_evaluation()
+    }
+
+    // Evaluates genomes concurrently on the rayon thread pool
+    #[cfg(feature = "parallel")]
+    pub fn evaluate_parallel<F: Fn(usize, &mut T) + Sync>(
+        &mut self,
+        evaluate: F,
+    ) -> Result<&T, NeatError>
+    where
+        T: Send,
+    {
+        self.start_generation();
+        let parsimony = self.params.parsimony;
+        self.list.par_iter_mut().enumerate().try_for_each(
+            |(i, network)| -> Result<(), NeatError> {
+                evaluate(i, network);
+                check_fitness(i, network)?;
+                penalize(network, &parsimony);
+                Ok(())
+            },
+        )?;
+
+        self.finish_evaluation()
+    }
+
+    // Evaluates genomes as CPPNs; the closure receives the substrate network decoded from each
+    // genome and returns its fitness
+    pub fn evaluate_substrate<F: Fn(usize, &SubstrateNetwork) -> f64>(
+        &mut self,
+        substrate: &Substrate,
+        evaluate: F,
+    ) -> Result<&T, NeatError> {
+        self.start_generation();
+        // Every genome is decoded first, so that no fitness is assigned if any of them fails
+        let networks = self
+            .list
+            .iter_mut()
+            .map(|cppn| substrate.decode(cppn))
+            .collect::<Result<Vec<SubstrateNetwork>, NeatError>>()?;
+
+        let parsimony = self.params.parsimony;
+        for (i, (cppn, network)) in self.list.iter_mut().zip(networks.iter()).enumerate() {
+            cppn.evaluate(evaluate(i, network));
+            penalize(cppn, &parsimony);
         }
-        let std_deviation = (delta_sum / list.len() as f64).sqrt();
 
-        (mean, std_deviation)
+        self.finish_evaluation()
     }
 
-    fn log_evaluation(&self, fitness_list: &[f64]) {
-        let (fitness_mean, fitness_std_deviation) = self.list_stats(&fitness_list);
+    // Behaviors are compared by distance, so they need one length and finite values
+    fn check_behaviors(&self, behaviors: &[Vec<f64>]) -> Result<(), NeatError> {
+        if let Some(i) = behaviors
+            .iter()
+            .position(|behavior| behavior.iter().any(|value| !value.is_finite()))
+        {
+            return Err(NeatError::InvalidBehavior(format!(
+                "behavior of genome {} has a non-finite value",
+                i
+            )));
+        }
 
-        self.log(1, &format!("[Generation {}]", self.generation));
+        let mut all = behaviors.iter().chain(self.novelty_archive.behaviors());
+        if let Some(dimension) = all.next().map(Vec::len) {
+            if let Some(behavior) = all.find(|behavior| behavior.len() != dimension) {
+                return Err(NeatError::InvalidBehavior(format!(
+                    "behaviors have different lengths, {} and {}",
+                    dimension,
+                    behavior.len()
+                )));
+            }
+        }
 
-        let message = &format!(
-            indoc! {"
-        # Evaluation result
-          - fitness max: {} ({} nodes, {} edges)
-          - fitness mean: {} (σ = {})
-        "},
-            fitness_list[0],
-            self.list[0].graph().node_count(),
-            self.list[0].graph().edge_count(),
-            fitness_mean,
-            fitness_std_deviation
-        );
-        self.log(1, message);
-        self.log(2, &format!("  - best genome: {:#?}", self.list[0]));
+        Ok(())
     }
 
-    fn log_speciation(
-        &self,
-        species_set: &[Species<T>],
-        adj_fitness_list: &[f64],
-        count_list: &[usize],
-    ) {
-        let mut speciation_log = format!(
-            indoc! {"
-            # Speciation result:
-              {:^6} | {:^5} | {:^6} | {:^11} | {:^10}
-              ====================================================
-            "},
-            "id", "age", "size", "offspring", "adj fit avg."
+    // Novelty search; the closure assigns fitness as usual and returns the behavior of the
+    // genome. Fitness is still what stats and reporters see, but genomes are selected in
+    // the next evolve by novelty blended with fitness according to the novelty parameters.
+    pub fn evaluate_novelty<F: Fn(usize, &mut T) -> Vec<f64>>(
+        &mut self,
+        evaluate: F,
+    ) -> Result<&T, NeatError> {
+        let novelty_params = self.params.novelty.ok_or_else(|| {
+            NeatError::InvalidParameters(vec![
+                "novelty parameters are required for novelty search".to_owned()
+            ])
+        })?;
+
+        self.start_generation();
+        let parsimony = self.params.parsimony;
+        let mut behaviors = Vec::with_capacity(self.list.len());
+        for (i, network) in self.list.iter_mut().enumerate() {
+            behaviors.push(evaluate(i, network));
+            check_fitness(i, network)?;
+            penalize(network, &parsimony);
+        }
+        self.check_behaviors(&behaviors)?;
+
+        // Sorted here so that behaviors stay aligned with genomes; the stable sort of
+        // finish_evaluation keeps this order
+        let mut evaluated: Vec<(T, Vec<f64>)> = self.list.drain(..).zip(behaviors).collect();
+        evaluated.sort_by(|(a, _), (b, _)| best_first(a, b, &parsimony));
+        let (list, behaviors): (Vec<T>, Vec<Vec<f64>>) = evaluated.into_iter().unzip();
+        self.list = list;
+
+        let novelty = self
+            .novelty_archive
+            .novelty(&behaviors, novelty_params.k_nearest);
+        self.novelty_archive.extend(
+            &behaviors,
+            &novelty,
+            novelty_params.archive_threshold,
+            novelty_params.archive_limit,
         );
-        for i in 0..species_set.len() {
-            speciation_log += &format!(
-                "  {:^6} | {:^5} | {:^6} | {:^11} | {:^10.4}\n",
-                species_set[i].id(),
-                species_set[i].age(),
-                species_set[i].genome_count(),
-                count_list[i],
-                adj_fitness_list[i]
-            );
+
+        // Fitness and novelty are normalized by their maximum before blending, as their
+        // scales are unrelated
+        let weight = novelty_params.novelty_weight;
+        if weight > 0.0 {
+            let max_fitness = self.list[0].fitness().unwrap();
+            let max_novelty = novelty.iter().cloned().fold(0.0, f64::max);
+            let normalize = |value: f64, max: f64| if max > 0.0 { value / max } else { 0.0 };
+
+            let scores = self
+                .list
+                .iter()
+                .zip(&novelty)
+                .map(|(network, &value)| {
+                    (1.0 - weight) * normalize(network.fitness().unwrap(), max_fitness)
+                        + weight * normalize(value, max_novelty)
+                })
+                .collect();
+            self.selection_scores = Some(scores);
         }
-        self.log(1, &speciation_log);
-    }
 
-    pub fn activate_nth(&mut self, index: usize, inputs: &[f64]) -> Option<Vec<f64>> {
-        self.list[index].activate(inputs)
+        self.finish_evaluation()
     }
 
-    pub fn evaluate<F: Fn(usize, &mut T)>(&mut self, evaluate: F) -> &T {
+    // Multi-objective evaluation; the closure assigns objectives with evaluate_objectives,
+    // every one of which is maximized. The first objective is assigned as fitness, which
+    // stats, reporters and offspring allocation between species use, while genomes inside
+    // each species survive by NSGA-II ranking in the next evolve.
+    pub fn evaluate_multi_objective<F: Fn(usize, &mut T)>(
+        &mut self,
+        evaluate: F,
+    ) -> Result<&T, NeatError> {
+        self.start_generation();
         for (i, network) in self.list.iter_mut().enumerate() {
+            // Elites would otherwise keep objectives of the previous generation
+            network.evaluate_objectives(Vec::new());
             evaluate(i, network);
-            assert!(network.fitness().is_some());
         }
 
-        self.list.sort_by(|a, b| b.compare(a).unwrap());
+        let objective_count = self.list.first().map_or(0, |n| n.objectives().len());
+        if let Some(network) = self
+            .list
+            .iter()
+            .find(|network| network.objectives().len() != objective_count)
+        {
+            return Err(NeatError::InvalidObjectives(format!(
+                "genomes have different numbers of objectives, {} and {}",
+                objective_count,
+                network.objectives().len()
+            )));
+        }
+        if objective_count == 0 {
+            return Err(NeatError::InvalidObjectives(
+                "genomes have no objectives".to_owned(),
+            ));
+        }
+        for (i, network) in self.list.iter().enumerate() {
+            let objectives = network.objectives();
+            if objectives.iter().any(|value| !value.is_finite()) {
+                return Err(NeatError::InvalidObjectives(format!(
+                    "objectives of genome {} have a non-finite value",
+                    i
+                )));
+            }
+            // The primary objective becomes fitness, which offspring are allocated by
+            if objectives[0] < 0.0 {
+                return Err(NeatError::InvalidObjectives(format!(
+                    "primary objective of genome {} is negative, {}",
+                    i, objectives[0]
+                )));
+            }
+        }
+
+        let parsimony = self.params.parsimony;
+        for network in &mut self.list {
+            let primary = network.objectives()[0];
+            network.evaluate(primary);
+            penalize(network, &parsimony);
+        }
+        self.multi_objective = true;
+
+        self.finish_evaluation()
+    }
+
+    // Genomes of the evaluated generation which no other genome dominates
+    pub fn pareto_front(&self) -> Vec<&T> {
+        let objectives: Vec<&[f64]> = self.list.iter().map(|n| n.objectives()).collect();
+        let ranks = pareto::pareto_ranks(&objectives);
+
+        self.list
+            .iter()
+            .zip(ranks)
+            .filter(|&(_, rank)| rank == 0)
+            .map(|(network, _)| network)
+            .collect()
+    }
+
+    pub fn novelty_archive(&self) -> &NoveltyArchive {
+        &self.novelty_archive
+    }
+
+    fn finish_evaluation(&mut self) -> Result<&T, NeatError> {
+        for (i, network) in self.list.iter().enumerate() {
+            check_fitness(i, network)?;
+        }
+        let parsimony = self.params.parsimony;
+        self.list.sort_by(|a, b| best_first(a, b, &parsimony));
+
+        let stats = self.evaluation_stats();
+        for reporter in &mut self.reporters {
+            reporter.on_evaluated(&stats, &self.list[0]);
+        }
 
-        let fitness_list: Vec<f64> = self.list.iter().map(|g| g.fitness().unwrap()).collect();
-        self.log_evaluation(&fitness_list);
+        if self
+            .best_fitness
+            .is_none_or(|best| stats.best_fitness > best)
+        {
+            self.best_fitness = Some(stats.best_fitness);
+            for reporter in &mut self.reporters {
+                reporter.on_new_best(self.generation, &self.list[0]);
+            }
+        }
+        self.stats = Some(stats);
 
-        &self.list[0]
+        Ok(&self.list[0])
     }
 
-    pub fn evolve(&mut self, innov_record: &mut InnovationRecord) {
-        let mut species_set = self.speciate(innov_record);
+    fn evaluation_stats(&self) -> GenerationStats {
+        let mut stats = GenerationStats::from_evaluation(
+            self.generation,
+            self.compatibility_threshold,
+            &self.list,
+        );
+        stats.phase = self.phase_state.phase();
+        stats
+    }
+
+    pub fn phase(&self) -> SearchPhase {
+        self.phase_state.phase()
+    }
+
+    // Stats of the last evaluation, without species until the generation is evolved
+    pub fn stats(&self) -> Option<&GenerationStats> {
+        self.stats.as_ref()
+    }
+
+    // Evaluates and evolves generations until the stop condition is met. The last generation
+    // is only evaluated, so a failing evolve can't discard the summary; call evolve to go on.
+    pub fn run<F: Fn(usize, &mut T)>(
+        &mut self,
+        innov_record: &mut InnovationRecord,
+        evaluate: F,
+        mut stop: StopCondition,
+    ) -> Result<RunSummary<T>, NeatError> {
+        if stop.is_empty() {
+            return Err(NeatError::InvalidParameters(vec![
+                "stop condition needs at least one criterion".to_owned(),
+            ]));
+        }
+
+        let start = Instant::now();
+        let mut champion: Option<T> = None;
+        let mut history = Vec::new();
+        let mut generations = 0;
+        loop {
+            let best = self.evaluate(&evaluate)?;
+            if champion
+                .as_ref()
+                .is_none_or(|champion| best.compare(champion) == Some(Ordering::Greater))
+            {
+                champion = Some(best.clone());
+            }
+
+            generations += 1;
+            let stats = self.stats.as_ref().unwrap();
+            if let Some(reason) = stop.check(stats, start.elapsed()) {
+                history.push(stats.clone());
+                return Ok(RunSummary {
+                    champion: champion.unwrap(),
+                    reason,
+                    generations,
+                    elapsed: start.elapsed(),
+                    history,
+                });
+            }
+
+            history.push(self.evolve(innov_record)?);
+        }
+    }
+
+    // Returns stats of the evolved generation, including its species
+    pub fn evolve(
+        &mut self,
+        innov_record: &mut InnovationRecord,
+    ) -> Result<GenerationStats, NeatError> {
+        // Offspring of the last evolve haven't been evaluated yet
+        for (i, network) in self.list.iter().enumerate() {
+            check_fitness(i, network)?;
+        }
+
+        if self.params.split_scope == SplitScope::Generation {
+            innov_record.clear_splits();
+        }
+
+        let mut stats = self.evaluation_stats();
+        if let Some(phased) = &self.params.phased_search {
+            stats.phase =
+                self.phase_state
+                    .update(phased, stats.mean_complexity, stats.best_fitness);
+        }
+
+        // Genomes keep objective fitness for stats, stagnation and elites, while selection
+        // inside species and offspring allocation use the scores
+        let scores: Vec<f64> = match self.selection_scores.take() {
+            Some(scores) => {
+                let tie_break = self.params.parsimony.tie_break;
+                let mut scored: Vec<(T, f64)> = self.list.drain(..).zip(scores).collect();
+                scored.sort_by(|(a, a_score), (b, b_score)| {
+                    b_score.total_cmp(a_score).then_with(|| {
+                        if tie_break {
+                            a.complexity().cmp(&b.complexity())
+                        } else {
+                            Ordering::Equal
+                        }
+                    })
+                });
+                let (list, scores) = scored.into_iter().unzip();
+                self.list = list;
+                scores
+            }
+            None => self.list.iter().map(|n| n.fitness().unwrap()).collect(),
+        };
+
+        let mut species_set = self.speciate(&scores, innov_record);
+        let species_count = species_set.iter().filter(|s| s.genome_count() > 0).count();
         for species in &mut species_set {
+            if self.multi_objective {
+                species.sort_by_pareto();
+            }
             species.kill_worst(self.params.speciation.survival_rate);
         }
 
-        species_set = species_set
-            .into_iter()
-            .filter(|s| s.genome_count() > 1)
+        // Reporters can't be borrowed mutably while species borrow the genome list
+        let formed: Vec<usize> = species_set
+            .iter()
+            .filter(|s| s.age() == 0)
+            .map(|s| s.id())
+            .collect();
+        let mut extinct: Vec<(usize, ExtinctionReason)> = species_set
+            .iter()
+            .filter(|s| s.genome_count() <= 1)
+            .map(|s| (s.id(), ExtinctionReason::TooSmall))
+            .collect();
+        species_set.retain(|s| s.genome_count() > 1);
+
+        let mut adj_fitness_list: Vec<f64> = species_set
+            .iter_mut()
+            .map(|s| s.update_adjusted_fitness().unwrap())
             .collect();
+        let stagnant = self.remove_stagnant_species(&mut species_set, &mut adj_fitness_list);
+        extinct.extend(
+            stagnant
+                .into_iter()
+                .map(|(id, generations)| (id, ExtinctionReason::Stagnation(generations))),
+        );
+
         if species_set.is_empty() {
-            panic!("remaining species_set size is 0; maybe compatibility threshold is too small?");
+            return Err(NeatError::ExtinctPopulation);
         }
 
         let mut offspring_list: Vec<T> = Vec::new();
@@ -254,56 +925,795 @@ impl<'a, T: Network + Debug + Clone> Pool<T> {
             offspring_list.extend(species.elites(self.params.speciation.elitism).to_owned());
         }
 
-        let target_count = self.params.population - offspring_list.len();
-        let adj_fitness_list: Vec<f64> = species_set
-            .iter_mut()
-            .map(|s| s.update_adjusted_fitness().unwrap())
-            .collect();
-        let adj_fitness_sum: f64 = adj_fitness_list.iter().sum();
-
-        let mut count_list: Vec<usize> = adj_fitness_list
+        let target_count = self.params.population.saturating_sub(offspring_list.len());
+        let count_list = allocate_offspring(&adj_fitness_list, target_count)?;
+        stats.species = species_set
             .iter()
-            .map(|f| (target_count as f64 * (f / adj_fitness_sum)).ceil() as usize)
+            .zip(&adj_fitness_list)
+            .zip(&count_list)
+            .map(|((species, &adjusted_fitness), &offspring)| SpeciesStats {
+                id: species.id(),
+                age: species.age(),
+                size: species.genome_count(),
+                offspring,
+                adjusted_fitness,
+                stagnation: species.stagnant(),
+            })
             .collect();
-        let total_count: usize = count_list.iter().sum();
-
-        for i in 0..total_count - target_count {
-            count_list[i % species_set.len()] -= 1;
-        }
-        self.log_speciation(&species_set, &adj_fitness_list, &count_list);
 
-        let rng = &mut rand::thread_rng();
+        // Species borrow the genome list, so the generator is copied out during reproduction
+        let mut rng = self.rng.clone();
         for (i, count) in count_list.into_iter().enumerate() {
             let species = &species_set[i];
             for _ in 0..count {
                 let mut offspring;
                 if species.genome_count() > 3
-                    && random01(rng) < self.params.reproduction.crossover_rate
+                    && random01(&mut rng) < self.params.reproduction.crossover_rate
                 {
-                    offspring = species
-                        .mate(
-                            rng,
-                            self.params.hidden_activation,
-                            self.params.output_activation,
-                        )
-                        .unwrap();
+                    offspring = species.mate(&mut rng)?;
+                    // Matching genes may come enabled from the other parent, going beyond the caps
+                    if exceeds_size_cap(&offspring, &self.params.parsimony) {
+                        offspring = species.random_genome(&mut rng);
+                    }
                 } else {
-                    offspring = species.random_genome(rng);
+                    offspring = species.random_genome(&mut rng);
                 }
 
-                self.mutate(&mut offspring, innov_record, rng);
+                self.mutate(&mut offspring, innov_record, &mut rng);
                 offspring_list.push(offspring);
             }
         }
 
         self.prev_species_info = species_set.into_iter().map(|s| s.info()).collect();
         self.list = offspring_list;
+        self.rng = rng;
+        self.adjust_compatibility_threshold(species_count);
+
+        for reporter in &mut self.reporters {
+            for &id in &formed {
+                reporter.on_species_formed(self.generation, id);
+            }
+            for &(id, reason) in &extinct {
+                reporter.on_species_extinct(self.generation, id, reason);
+            }
+            reporter.on_generation_end(&stats);
+        }
 
-        self.log(1, "\n---------------------------------\n");
         self.generation += 1;
+        self.stats = None;
+        Ok(stats)
     }
 
     pub fn generation(&self) -> usize {
         self.generation
     }
+
+    // Threshold used for the next speciation, which differs from the parameter when
+    // dynamic_threshold is set
+    pub fn compatibility_threshold(&self) -> f64 {
+        self.compatibility_threshold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        activations::ActivationKind,
+        network::{feedforward::Feedforward, recurrent::Recurrent},
+        parameters::{
+            DynamicThresholdParameters, MutationParameters, NoveltyParameters,
+            PhasedSearchParameters, ReproductionParameters, SpeciationParameters,
+        },
+        termination::StopReason,
+    };
+
+    fn xor_parameters() -> Parameters {
+        Parameters::builder(2, 1)
+            .population(50)
+            .mutation(MutationParameters {
+                add_connection: 0.5,
+                add_node: 0.2,
+                ..Default::default()
+            })
+            .speciation(SpeciationParameters {
+                c2: 0.5,
+                compatibility_threshold: 15.0,
+                survival_rate: 0.5,
+                elitism: 2,
+                max_stagnation: None,
+                species_elitism: 0,
+                ..Default::default()
+            })
+            .reproduction(ReproductionParameters {
+                crossover_rate: 0.3,
+            })
+            .build()
+            .unwrap()
+    }
+
+    fn xor_fitness(_: usize, network: &mut Feedforward) {
+        let data = [
+            ([0.0, 0.0], 0.0),
+            ([0.0, 1.0], 1.0),
+            ([1.0, 0.0], 1.0),
+            ([1.0, 1.0], 0.0),
+        ];
+
+        let mut err = 0.0;
+        for (inputs, expected) in &data {
+            let output = network.activate(inputs).unwrap()[0];
+            err += (output - expected) * (output - expected);
+        }
+        network.evaluate(4.0 - err);
+    }
+
+    fn xor_behavior(i: usize, network: &mut Feedforward) -> Vec<f64> {
+        xor_fitness(i, network);
+        let inputs: [&[f64]; 4] = [&[0.0, 0.0], &[0.0, 1.0], &[1.0, 0.0], &[1.0, 1.0]];
+        network
+            .activate_batch(&inputs)
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    fn run(pool: &mut Pool<Feedforward>, innov_record: &mut InnovationRecord, generations: usize) {
+        for _ in 0..generations {
+            pool.evaluate(xor_fitness).unwrap();
+            pool.evolve(innov_record).unwrap();
+        }
+    }
+
+    #[test]
+    fn resumed_pool_should_continue_identically() {
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let mut pool = Pool::<Feedforward>::with_seed(xor_parameters(), 0, 1, &mut innov_record);
+        run(&mut pool, &mut innov_record, 5);
+
+        let serialized =
+            serialization::to_string(&pool.checkpoint(&innov_record), Format::Json).unwrap();
+        let checkpoint: Checkpoint<Feedforward> =
+            serialization::from_str(&serialized, Format::Json).unwrap();
+        assert_eq!(checkpoint.generation(), pool.generation());
+        let (mut resumed, mut resumed_record) = Pool::from_checkpoint(checkpoint, 0);
+        // Reporters aren't told of a new best that isn't better than before the checkpoint
+        assert_eq!(resumed.best_fitness, pool.best_fitness);
+
+        run(&mut pool, &mut innov_record, 5);
+        run(&mut resumed, &mut resumed_record, 5);
+
+        assert_eq!(
+            serialization::to_string(&pool.checkpoint(&innov_record), Format::Json).unwrap(),
+            serialization::to_string(&resumed.checkpoint(&resumed_record), Format::Json).unwrap()
+        );
+    }
+
+    #[test]
+    fn checkpoint_between_evaluation_and_evolve_should_resume_identically() {
+        let mut params = xor_parameters();
+        params.novelty = Some(NoveltyParameters {
+            k_nearest: 5,
+            archive_threshold: 0.05,
+            archive_limit: None,
+            novelty_weight: 0.8,
+        });
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let mut pool = Pool::<Feedforward>::with_seed(params, 0, 1, &mut innov_record);
+        for _ in 0..3 {
+            pool.evaluate_novelty(xor_behavior).unwrap();
+            pool.evolve(&mut innov_record).unwrap();
+        }
+        pool.evaluate_novelty(xor_behavior).unwrap();
+
+        let serialized =
+            serialization::to_string(&pool.checkpoint(&innov_record), Format::Json).unwrap();
+        let checkpoint: Checkpoint<Feedforward> =
+            serialization::from_str(&serialized, Format::Json).unwrap();
+        let (mut resumed, mut resumed_record) = Pool::from_checkpoint(checkpoint, 0);
+
+        let stats = pool.evolve(&mut innov_record).unwrap();
+        assert_eq!(resumed.evolve(&mut resumed_record).unwrap(), stats);
+        assert_eq!(
+            serialization::to_string(&pool.checkpoint(&innov_record), Format::Json).unwrap(),
+            serialization::to_string(&resumed.checkpoint(&resumed_record), Format::Json).unwrap()
+        );
+    }
+
+    #[test]
+    fn checkpoint_should_only_be_saved_as_json() {
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let pool = Pool::<Feedforward>::with_seed(xor_parameters(), 0, 1, &mut innov_record);
+        let path =
+            std::env::temp_dir().join(format!("neat-checkpoint-{}.toml", std::process::id()));
+
+        assert!(matches!(
+            pool.save_checkpoint(&path, &innov_record),
+            Err(NeatError::Serialization(
+                SerializationError::UnsupportedFormat(_)
+            ))
+        ));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn recurrent_steps_should_be_kept_across_generations() {
+        let mut params = xor_parameters();
+        params.recurrent_steps = 3;
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let mut pool = Pool::<Recurrent>::with_seed(params, 0, 1, &mut innov_record);
+
+        for _ in 0..3 {
+            assert!(pool.list.iter().all(|network| network.steps() == 3));
+            pool.evaluate(|_, network| network.evaluate(network.graph().edge_count() as f64))
+                .unwrap();
+            pool.evolve(&mut innov_record).unwrap();
+        }
+    }
+
+    #[test]
+    fn same_seed_should_produce_identical_generations() {
+        let mut params = xor_parameters();
+        params.seed = Some(42);
+
+        let mut innov_record1 = InnovationRecord::new(2, 1);
+        let mut pool1 = Pool::<Feedforward>::new(params, 0, &mut innov_record1);
+        let mut innov_record2 = InnovationRecord::new(2, 1);
+        let mut pool2 = Pool::<Feedforward>::with_seed(xor_parameters(), 0, 42, &mut innov_record2);
+
+        run(&mut pool1, &mut innov_record1, 10);
+        run(&mut pool2, &mut innov_record2, 10);
+
+        let checkpoint1 = pool1.checkpoint(&innov_record1);
+        let mut checkpoint2 = pool2.checkpoint(&innov_record2);
+        checkpoint2.params.seed = Some(42);
+        assert_eq!(
+            serialization::to_string(&checkpoint1, Format::Json).unwrap(),
+            serialization::to_string(&checkpoint2, Format::Json).unwrap()
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_evaluation_should_match_serial_evaluation() {
+        let mut innov_record1 = InnovationRecord::new(2, 1);
+        let mut pool1 = Pool::<Feedforward>::with_seed(xor_parameters(), 0, 7, &mut innov_record1);
+        let mut innov_record2 = InnovationRecord::new(2, 1);
+        let mut pool2 = Pool::<Feedforward>::with_seed(xor_parameters(), 0, 7, &mut innov_record2);
+
+        for _ in 0..5 {
+            pool1.evaluate(xor_fitness).unwrap();
+            pool1.evolve(&mut innov_record1).unwrap();
+            pool2.evaluate_parallel(xor_fitness).unwrap();
+            pool2.evolve(&mut innov_record2).unwrap();
+        }
+
+        assert_eq!(
+            serialization::to_string(&pool1.checkpoint(&innov_record1), Format::Json).unwrap(),
+            serialization::to_string(&pool2.checkpoint(&innov_record2), Format::Json).unwrap()
+        );
+    }
+
+    #[test]
+    fn substrate_evaluation_should_receive_decoded_network() {
+        let substrate = Substrate {
+            layers: vec![vec![vec![-1.0], vec![1.0]], vec![vec![0.0]]],
+            weight_threshold: 0.2,
+            max_weight: 3.0,
+            hidden_activation: ActivationKind::Sigmoid,
+            output_activation: ActivationKind::Linear,
+        };
+        let mut params = xor_parameters();
+        params.output_activation = ActivationKind::Tanh;
+
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let mut pool = Pool::<Feedforward>::with_seed(params, 0, 3, &mut innov_record);
+
+        for _ in 0..5 {
+            let best = pool
+                .evaluate_substrate(&substrate, |_, network| {
+                    assert_eq!(network.layer_sizes(), &[2, 1]);
+                    network.connection_count() as f64
+                })
+                .unwrap();
+            assert!(best.fitness().unwrap() <= 2.0);
+            pool.evolve(&mut innov_record).unwrap();
+        }
+    }
+
+    #[test]
+    fn stagnant_species_should_be_removed_except_best() {
+        let mut params = xor_parameters();
+        params.speciation.max_stagnation = Some(2);
+        params.speciation.species_elitism = 1;
+
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let pool: Pool<Feedforward> = Pool::with_seed(params, 0, 0, &mut innov_record);
+
+        let networks: Vec<Feedforward> = [1.0, 3.0, 2.0]
+            .iter()
+            .map(|&fitness| {
+                let mut network =
+                    Feedforward::new(2, 1, ActivationKind::Sigmoid, &mut innov_record);
+                network.evaluate(fitness);
+                network
+            })
+            .collect();
+        let mut species_set: Vec<Species<Feedforward>> = networks
+            .iter()
+            .enumerate()
+            .map(|(id, network)| {
+                let mut species = Species::new(SpeciesInfo::new(id, network.clone(), 0));
+                species.force_assign(network, network.fitness().unwrap());
+                species
+            })
+            .collect();
+
+        // Fitness never changes, so every species is stagnant from the second generation
+        let mut adj_fitness_list = Vec::new();
+        for generation in 0..3 {
+            adj_fitness_list = species_set
+                .iter_mut()
+                .map(|s| s.update_adjusted_fitness().unwrap())
+                .collect();
+
+            if generation == 1 {
+                let mut unchanged = species_set.clone();
+                pool.remove_stagnant_species(&mut unchanged, &mut adj_fitness_list.clone());
+                assert_eq!(unchanged.len(), 3);
+            }
+        }
+
+        pool.remove_stagnant_species(&mut species_set, &mut adj_fitness_list);
+        let ids: Vec<usize> = species_set.iter().map(|s| s.id()).collect();
+        assert_eq!(ids, vec![1]);
+        assert_eq!(adj_fitness_list, vec![3.0]);
+    }
+
+    #[test]
+    fn growing_species_should_not_be_stagnant_while_improving() {
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let mut networks: Vec<Feedforward> = (0..3)
+            .map(|_| Feedforward::new(2, 1, ActivationKind::Sigmoid, &mut innov_record))
+            .collect();
+        networks[0].evaluate(2.0);
+        networks[1].evaluate(3.0);
+        networks[2].evaluate(3.0);
+
+        let mut species = Species::new(SpeciesInfo::new(0, networks[0].clone(), 0));
+        species.force_assign(&networks[0], 2.0);
+        assert_eq!(species.update_adjusted_fitness(), Some(2.0));
+
+        // Adjusted fitness drops from 2 to 1.5, while best fitness improves
+        let mut species = Species::new(species.info());
+        species.force_assign(&networks[1], 3.0);
+        species.force_assign(&networks[2], 3.0);
+        assert_eq!(species.update_adjusted_fitness(), Some(1.5));
+        assert_eq!(species.stagnant(), 0);
+
+        let mut species = Species::new(species.info());
+        species.force_assign(&networks[0], 2.0);
+        species.update_adjusted_fitness();
+        assert_eq!(species.stagnant(), 1);
+    }
+
+    #[test]
+    fn dynamic_threshold_should_move_toward_target_species_count() {
+        let mut params = xor_parameters();
+        params.speciation.dynamic_threshold = Some(DynamicThresholdParameters {
+            target_species_count: 50,
+            step: 2.0,
+            threshold_min: 10.0,
+            threshold_max: 20.0,
+        });
+
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let mut pool: Pool<Feedforward> = Pool::with_seed(params, 0, 0, &mut innov_record);
+        assert_eq!(pool.compatibility_threshold(), 15.0);
+
+        // Target is unreachable at these thresholds, so the threshold keeps decreasing to the bound
+        run(&mut pool, &mut innov_record, 1);
+        assert_eq!(pool.compatibility_threshold(), 13.0);
+        run(&mut pool, &mut innov_record, 3);
+        assert_eq!(pool.compatibility_threshold(), 10.0);
+    }
+
+    #[test]
+    fn evolve_should_fail_when_every_species_dies_out() {
+        let mut params = xor_parameters();
+        // Every genome forms its own species, and a species of one genome doesn't survive
+        params.speciation.compatibility_threshold = -1.0;
+
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let mut pool: Pool<Feedforward> = Pool::with_seed(params, 0, 0, &mut innov_record);
+        pool.evaluate(xor_fitness).unwrap();
+
+        assert!(matches!(
+            pool.evolve(&mut innov_record),
+            Err(NeatError::ExtinctPopulation)
+        ));
+    }
+
+    #[test]
+    fn missing_or_non_finite_fitness_should_be_rejected() {
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let mut pool: Pool<Feedforward> =
+            Pool::with_seed(xor_parameters(), 0, 0, &mut innov_record);
+
+        assert!(matches!(
+            pool.evaluate(|i, network| if i != 3 {
+                network.evaluate(1.0)
+            }),
+            Err(NeatError::InvalidFitness(_))
+        ));
+        assert!(matches!(
+            pool.evaluate(|_, network| network.evaluate(f64::INFINITY)),
+            Err(NeatError::InvalidFitness(_))
+        ));
+        assert!(matches!(
+            pool.evaluate(|_, network| network.evaluate(f64::NAN)),
+            Err(NeatError::InvalidFitness(_))
+        ));
+
+        let mut pool: Pool<Feedforward> =
+            Pool::with_seed(xor_parameters(), 0, 0, &mut innov_record);
+        assert!(matches!(
+            pool.evolve(&mut innov_record),
+            Err(NeatError::InvalidFitness(_))
+        ));
+        pool.evaluate(xor_fitness).unwrap();
+        pool.evolve(&mut innov_record).unwrap();
+        assert!(matches!(
+            pool.evolve(&mut innov_record),
+            Err(NeatError::InvalidFitness(_))
+        ));
+    }
+
+    #[test]
+    fn offspring_should_be_split_evenly_without_adjusted_fitness() {
+        assert_eq!(allocate_offspring(&[1.0, 3.0], 8).unwrap(), vec![2, 6]);
+        assert_eq!(
+            allocate_offspring(&[0.0, 0.0, 0.0], 10).unwrap(),
+            vec![3, 3, 4]
+        );
+        assert_eq!(allocate_offspring(&[0.0, 0.0], 0).unwrap(), vec![0, 0]);
+        assert!(matches!(
+            allocate_offspring(&[1.0, f64::INFINITY], 10),
+            Err(NeatError::InvalidFitness(_))
+        ));
+
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let mut pool: Pool<Feedforward> =
+            Pool::with_seed(xor_parameters(), 0, 0, &mut innov_record);
+        for _ in 0..3 {
+            pool.evaluate(|_, network| network.evaluate(0.0)).unwrap();
+            pool.evolve(&mut innov_record).unwrap();
+            assert_eq!(pool.list.len(), 50);
+        }
+    }
+
+    #[test]
+    fn evolve_should_return_stats_of_generation() {
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let mut pool: Pool<Feedforward> =
+            Pool::with_seed(xor_parameters(), 0, 5, &mut innov_record);
+
+        let best_fitness = pool.evaluate(xor_fitness).unwrap().fitness().unwrap();
+        let evaluation_stats = pool.stats().unwrap().clone();
+        assert_eq!(evaluation_stats.best_fitness, best_fitness);
+        assert!(evaluation_stats.species.is_empty());
+
+        let stats = pool.evolve(&mut innov_record).unwrap();
+        assert_eq!(stats.generation, 1);
+        assert_eq!(stats.mean_fitness, evaluation_stats.mean_fitness);
+        assert!(stats.median_fitness <= stats.best_fitness);
+        assert!(!stats.species.is_empty());
+
+        // Offspring fill the population together with the elites of each species
+        let elites: usize = stats.species.iter().map(|s| s.size.min(2)).sum();
+        let offspring: usize = stats.species.iter().map(|s| s.offspring).sum();
+        assert_eq!(elites + offspring, 50);
+        assert!(pool.stats().is_none());
+    }
+
+    #[test]
+    fn reporters_should_be_notified_of_generation_events() {
+        use std::{cell::RefCell, rc::Rc};
+
+        struct Recorder(Rc<RefCell<Vec<String>>>);
+        impl Reporter<Feedforward> for Recorder {
+            fn on_generation_start(&mut self, generation: usize) {
+                self.0.borrow_mut().push(format!("start {}", generation));
+            }
+            fn on_evaluated(&mut self, stats: &GenerationStats, best: &Feedforward) {
+                assert_eq!(stats.best_fitness, best.fitness().unwrap());
+                self.0.borrow_mut().push("evaluated".to_owned());
+            }
+            fn on_species_formed(&mut self, _: usize, _: usize) {
+                self.0.borrow_mut().push("formed".to_owned());
+            }
+            fn on_new_best(&mut self, _: usize, _: &Feedforward) {
+                self.0.borrow_mut().push("new best".to_owned());
+            }
+            fn on_generation_end(&mut self, stats: &GenerationStats) {
+                self.0
+                    .borrow_mut()
+                    .push(format!("end {}", stats.generation));
+            }
+        }
+
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let mut pool: Pool<Feedforward> =
+            Pool::with_seed(xor_parameters(), 0, 0, &mut innov_record);
+        pool.add_reporter(Recorder(events.clone()));
+
+        run(&mut pool, &mut innov_record, 1);
+        let events = events.borrow();
+        assert_eq!(events[..3], ["start 1", "evaluated", "new best"]);
+        assert!(events[3..events.len() - 1].iter().all(|e| e == "formed"));
+        assert!(events.len() > 4);
+        assert_eq!(events.last().unwrap(), "end 1");
+    }
+
+    #[test]
+    fn run_should_stop_when_criterion_is_met() {
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let mut pool: Pool<Feedforward> =
+            Pool::with_seed(xor_parameters(), 0, 0, &mut innov_record);
+
+        let summary = pool
+            .run(
+                &mut innov_record,
+                xor_fitness,
+                StopCondition::new()
+                    .fitness_threshold(4.0)
+                    .max_generations(5),
+            )
+            .unwrap();
+        assert_eq!(summary.reason, StopReason::MaxGenerations);
+        assert_eq!(summary.generations, 5);
+        assert_eq!(summary.history.len(), 5);
+        assert_eq!(pool.generation(), 5);
+        assert!(summary.history[4].species.is_empty());
+
+        let best_fitness = summary
+            .history
+            .iter()
+            .map(|stats| stats.best_fitness)
+            .fold(0.0, f64::max);
+        assert_eq!(summary.champion.fitness(), Some(best_fitness));
+
+        let summary = pool
+            .run(
+                &mut innov_record,
+                xor_fitness,
+                StopCondition::new().fitness_threshold(0.0),
+            )
+            .unwrap();
+        assert_eq!(summary.reason, StopReason::FitnessThreshold);
+        assert_eq!(summary.generations, 1);
+
+        assert!(matches!(
+            pool.run(&mut innov_record, xor_fitness, StopCondition::new()),
+            Err(NeatError::InvalidParameters(_))
+        ));
+    }
+
+    #[test]
+    fn novelty_search_should_archive_behaviors_and_keep_objective_stats() {
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let mut pool: Pool<Feedforward> =
+            Pool::with_seed(xor_parameters(), 0, 0, &mut innov_record);
+        assert!(matches!(
+            pool.evaluate_novelty(xor_behavior),
+            Err(NeatError::InvalidParameters(_))
+        ));
+
+        let mut params = xor_parameters();
+        params.novelty = Some(NoveltyParameters {
+            k_nearest: 5,
+            archive_threshold: 0.05,
+            archive_limit: Some(30),
+            novelty_weight: 0.8,
+        });
+        let mut pool: Pool<Feedforward> = Pool::with_seed(params, 0, 0, &mut innov_record);
+        for _ in 0..5 {
+            let best_fitness = pool.evaluate_novelty(xor_behavior).unwrap().fitness();
+            let stats = pool.evolve(&mut innov_record).unwrap();
+            assert_eq!(Some(stats.best_fitness), best_fitness);
+        }
+
+        assert!(!pool.novelty_archive().is_empty());
+        assert!(pool.novelty_archive().len() <= 30);
+
+        // Selection scores never replace fitness, so elites carry their objective fitness
+        let constant_behavior = |i: usize, network: &mut Feedforward| {
+            let behavior = xor_behavior(i, network);
+            network.evaluate(3.0);
+            behavior
+        };
+        pool.evaluate_novelty(constant_behavior).unwrap();
+        pool.evolve(&mut innov_record).unwrap();
+        assert!(pool
+            .list
+            .iter()
+            .all(|n| n.fitness().is_none_or(|f| f == 3.0)));
+
+        let nan_behavior = |i: usize, network: &mut Feedforward| {
+            let mut behavior = xor_behavior(i, network);
+            behavior[0] = f64::NAN;
+            behavior
+        };
+        let short_behavior = |i: usize, network: &mut Feedforward| {
+            let mut behavior = xor_behavior(i, network);
+            behavior.pop();
+            behavior
+        };
+        assert!(matches!(
+            pool.evaluate_novelty(nan_behavior),
+            Err(NeatError::InvalidBehavior(_))
+        ));
+        assert!(matches!(
+            pool.evaluate_novelty(short_behavior),
+            Err(NeatError::InvalidBehavior(_))
+        ));
+    }
+
+    #[test]
+    fn multi_objective_evaluation_should_rank_genomes_by_pareto_front() {
+        let xor_objectives = |i: usize, network: &mut Feedforward| {
+            xor_fitness(i, network);
+            let objectives = vec![
+                network.fitness().unwrap(),
+                -(network.graph().edge_count() as f64),
+            ];
+            network.evaluate_objectives(objectives);
+        };
+
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let mut pool: Pool<Feedforward> =
+            Pool::with_seed(xor_parameters(), 0, 0, &mut innov_record);
+        for _ in 0..5 {
+            let best_fitness = pool
+                .evaluate_multi_objective(xor_objectives)
+                .unwrap()
+                .fitness()
+                .unwrap();
+
+            let front = pool.pareto_front();
+            assert!(front.iter().any(|n| n.fitness() == Some(best_fitness)));
+            for a in &front {
+                assert!(!pool
+                    .list
+                    .iter()
+                    .any(|b| pareto::dominates(b.objectives(), a.objectives())));
+            }
+
+            pool.evolve(&mut innov_record).unwrap();
+        }
+
+        assert!(matches!(
+            pool.evaluate_multi_objective(xor_fitness),
+            Err(NeatError::InvalidObjectives(_))
+        ));
+        assert!(matches!(
+            pool.evaluate_multi_objective(|_, network| {
+                network.evaluate_objectives(vec![1.0, f64::NAN])
+            }),
+            Err(NeatError::InvalidObjectives(_))
+        ));
+        assert!(matches!(
+            pool.evaluate_multi_objective(|_, network| {
+                network.evaluate_objectives(vec![-1.0, 0.0])
+            }),
+            Err(NeatError::InvalidObjectives(_))
+        ));
+    }
+
+    #[test]
+    fn parsimony_should_penalize_and_cap_genomes() {
+        let mut params = xor_parameters();
+        params.mutation.add_node = 0.8;
+        params.parsimony = ParsimonyParameters {
+            tie_break: true,
+            node_penalty: 0.2,
+            edge_penalty: 0.1,
+            max_hidden_nodes: Some(2),
+            max_edges: None,
+        };
+
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let mut pool: Pool<Feedforward> = Pool::with_seed(params, 0, 0, &mut innov_record);
+        for _ in 0..10 {
+            pool.evaluate(xor_fitness).unwrap();
+            for network in &pool.list {
+                let mut unpenalized = network.clone();
+                xor_fitness(0, &mut unpenalized);
+                let penalty = 0.2 * network.graph().hidden_node_count() as f64
+                    + 0.1 * network.graph().enabled_edge_count() as f64;
+
+                assert_eq!(
+                    network.fitness(),
+                    Some((unpenalized.fitness().unwrap() - penalty).max(0.0))
+                );
+                assert!(network.graph().hidden_node_count() <= 2);
+            }
+            pool.evolve(&mut innov_record).unwrap();
+        }
+    }
+
+    #[test]
+    fn crossover_children_should_be_capped() {
+        let mut params = xor_parameters();
+        params.mutation.add_node = 0.5;
+        params.mutation.toggle_connection = 0.5;
+        params.reproduction.crossover_rate = 1.0;
+        params.parsimony.max_edges = Some(4);
+
+        // Matching genes may be enabled in either parent, so children can have more enabled
+        // edges than both
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let mut pool: Pool<Feedforward> = Pool::with_seed(params, 0, 0, &mut innov_record);
+        for _ in 0..10 {
+            pool.evaluate(|_, network| network.evaluate(1.0)).unwrap();
+            assert!(pool
+                .list
+                .iter()
+                .all(|network| network.graph().enabled_edge_count() <= 4));
+            pool.evolve(&mut innov_record).unwrap();
+        }
+    }
+
+    #[test]
+    fn dominant_penalty_should_not_stop_evolution() {
+        let mut params = xor_parameters();
+        params.parsimony.edge_penalty = 5.0;
+
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let mut pool: Pool<Feedforward> = Pool::with_seed(params, 0, 0, &mut innov_record);
+        for _ in 0..5 {
+            pool.evaluate(|_, network| network.evaluate(1.0)).unwrap();
+            assert!(pool
+                .list
+                .iter()
+                .all(|network| network.fitness() == Some(0.0)));
+
+            let stats = pool.evolve(&mut innov_record).unwrap();
+            let offspring: usize = stats.species.iter().map(|s| s.offspring).sum();
+            assert!(offspring > 0);
+        }
+        assert_eq!(pool.list.len(), 50);
+    }
+
+    #[test]
+    fn phased_search_should_alternate_phases() {
+        let mut params = xor_parameters();
+        params.mutation.add_node = 0.5;
+        params.phased_search = Some(PhasedSearchParameters {
+            complexity_threshold: 1.0,
+            fitness_plateau: None,
+            simplification_plateau: 3,
+            remove_node: 0.3,
+            remove_connection: 0.3,
+        });
+
+        let mut innov_record = InnovationRecord::new(2, 1);
+        let mut pool: Pool<Feedforward> = Pool::with_seed(params, 0, 0, &mut innov_record);
+        let mut phases = Vec::new();
+        for _ in 0..30 {
+            pool.evaluate(xor_fitness).unwrap();
+            let stats = pool.evolve(&mut innov_record).unwrap();
+            assert_eq!(stats.phase, pool.phase());
+            phases.push(stats.phase);
+        }
+
+        let simplifying = phases.iter().position(|&p| p == SearchPhase::Simplifying);
+        assert!(simplifying.is_some_and(|i| phases[i..].contains(&SearchPhase::Complexifying)));
+
+        let checkpoint = pool.checkpoint(&innov_record);
+        let (resumed, _) = Pool::from_checkpoint(checkpoint, 0);
+        assert_eq!(resumed.phase(), pool.phase());
+    }
 }
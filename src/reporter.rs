@@ -0,0 +1,117 @@
+use std::fmt::Debug;
+
+use indoc::indoc;
+
+use crate::{network::Network, stats::GenerationStats};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExtinctionReason {
+    // Less than two genomes of the species survived
+    TooSmall,
+
+    // Best fitness hasn't improved for the given number of generations
+    Stagnation(usize),
+}
+
+// Observer of an evolution run attached with Pool::add_reporter. Every callback does nothing
+// by default, so reporters only implement what they need.
+pub trait Reporter<T> {
+    fn on_generation_start(&mut self, _generation: usize) {}
+    fn on_evaluated(&mut self, _stats: &GenerationStats, _best: &T) {}
+
+    fn on_species_formed(&mut self, _generation: usize, _id: usize) {}
+    fn on_species_extinct(&mut self, _generation: usize, _id: usize, _reason: ExtinctionReason) {}
+
+    // Called when the best genome of a generation beats every previous one
+    fn on_new_best(&mut self, _generation: usize, _best: &T) {}
+    fn on_generation_end(&mut self, _stats: &GenerationStats) {}
+}
+
+// Prints the progress of the run; nothing is printed with verbosity 0
+pub struct StdoutReporter {
+    verbosity: usize,
+
+    // Id and stagnation of species removed in the current generation, printed after the
+    // speciation result so that the log stays parsable by analysis/main.py
+    removed: Vec<(usize, usize)>,
+}
+
+impl StdoutReporter {
+    pub fn new(verbosity: usize) -> Self {
+        Self {
+            verbosity,
+            removed: Vec::new(),
+        }
+    }
+
+    fn log(&self, verbosity: usize, message: &str) {
+        if verbosity <= self.verbosity {
+            println!("{}", message);
+        }
+    }
+}
+
+impl<T: Network + Debug> Reporter<T> for StdoutReporter {
+    fn on_generation_start(&mut self, generation: usize) {
+        self.log(1, &format!("[Generation {}]", generation));
+    }
+
+    fn on_evaluated(&mut self, stats: &GenerationStats, best: &T) {
+        let message = &format!(
+            indoc! {"
+        # Evaluation result
+          - fitness max: {} ({} nodes, {} edges)
+          - fitness mean: {} (σ = {})
+        "},
+            stats.best_fitness,
+            stats.best_node_count,
+            stats.best_edge_count,
+            stats.mean_fitness,
+            stats.std_fitness
+        );
+        self.log(1, message);
+        self.log(2, &format!("  - best genome: {:#?}", best));
+    }
+
+    fn on_species_extinct(&mut self, _generation: usize, id: usize, reason: ExtinctionReason) {
+        if let ExtinctionReason::Stagnation(generations) = reason {
+            self.removed.push((id, generations));
+        }
+    }
+
+    fn on_generation_end(&mut self, stats: &GenerationStats) {
+        let mut speciation_log = format!(
+            indoc! {"
+            # Speciation result:
+              {:^6} | {:^5} | {:^6} | {:^11} | {:^10}
+              ====================================================
+            "},
+            "id", "age", "size", "offspring", "adj fit avg."
+        );
+        for species in &stats.species {
+            speciation_log += &format!(
+                "  {:^6} | {:^5} | {:^6} | {:^11} | {:^10.4}\n",
+                species.id, species.age, species.size, species.offspring, species.adjusted_fitness
+            );
+        }
+        self.log(1, &speciation_log);
+        self.log(
+            2,
+            &format!(
+                "# Compatibility threshold: {:.4}",
+                stats.compatibility_threshold
+            ),
+        );
+        for (id, generations) in std::mem::take(&mut self.removed) {
+            self.log(
+                2,
+                &format!(
+                    "# Species {} removed: no improvement for {} generations",
+                    id, generations
+                ),
+            );
+        }
+
+        self.log(1, "\n---------------------------------\n");
+    }
+}
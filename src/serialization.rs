@@ -0,0 +1,232 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    fs, io,
+    path::Path,
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Json,
+    Toml,
+}
+
+impl Format {
+    // Guesses the format from the extension of the path
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "json" => Some(Format::Json),
+            "toml" => Some(Format::Toml),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SerializationError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    TomlSerialize(toml::ser::Error),
+    TomlDeserialize(toml::de::Error),
+    UnknownFormat(String),
+    // Format of the path can't represent the value
+    UnsupportedFormat(String),
+}
+
+impl Display for SerializationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SerializationError::Io(e) => write!(f, "I/O error: {}", e),
+            SerializationError::Json(e) => write!(f, "JSON error: {}", e),
+            SerializationError::TomlSerialize(e) => write!(f, "TOML serialization error: {}", e),
+            SerializationError::TomlDeserialize(e) => {
+                write!(f, "TOML deserialization error: {}", e)
+            }
+            SerializationError::UnknownFormat(path) => {
+                write!(f, "couldn't guess format of '{}'", path)
+            }
+            SerializationError::UnsupportedFormat(path) => {
+                write!(f, "format of '{}' isn't supported, use .json", path)
+            }
+        }
+    }
+}
+
+impl Error for SerializationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SerializationError::Io(e) => Some(e),
+            SerializationError::Json(e) => Some(e),
+            SerializationError::TomlSerialize(e) => Some(e),
+            SerializationError::TomlDeserialize(e) => Some(e),
+            SerializationError::UnknownFormat(_) | SerializationError::UnsupportedFormat(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for SerializationError {
+    fn from(e: io::Error) -> Self {
+        SerializationError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for SerializationError {
+    fn from(e: serde_json::Error) -> Self {
+        SerializationError::Json(e)
+    }
+}
+
+impl From<toml::ser::Error> for SerializationError {
+    fn from(e: toml::ser::Error) -> Self {
+        SerializationError::TomlSerialize(e)
+    }
+}
+
+impl From<toml::de::Error> for SerializationError {
+    fn from(e: toml::de::Error) -> Self {
+        SerializationError::TomlDeserialize(e)
+    }
+}
+
+pub fn to_string<T: Serialize>(value: &T, format: Format) -> Result<String, SerializationError> {
+    match format {
+        Format::Json => Ok(serde_json::to_string_pretty(value)?),
+        Format::Toml => Ok(toml::to_string(value)?),
+    }
+}
+
+pub fn from_str<T: DeserializeOwned>(s: &str, format: Format) -> Result<T, SerializationError> {
+    match format {
+        Format::Json => Ok(serde_json::from_str(s)?),
+        Format::Toml => Ok(toml::from_str(s)?),
+    }
+}
+
+fn path_format(path: &Path) -> Result<Format, SerializationError> {
+    Format::from_path(path)
+        .ok_or_else(|| SerializationError::UnknownFormat(path.display().to_string()))
+}
+
+// Saves the value to the file, in the format guessed from the extension (.json or .toml)
+pub fn save<T: Serialize>(value: &T, path: impl AsRef<Path>) -> Result<(), SerializationError> {
+    let path = path.as_ref();
+    fs::write(path, to_string(value, path_format(path)?)?)?;
+    Ok(())
+}
+
+// Loads the value from the file, in the format guessed from the extension (.json or .toml)
+pub fn load<T: DeserializeOwned>(path: impl AsRef<Path>) -> Result<T, SerializationError> {
+    let path = path.as_ref();
+    let format = path_format(path)?;
+    from_str(&fs::read_to_string(path)?, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        activations::ActivationKind,
+        innovation_record::InnovationRecord,
+        network::{feedforward::Feedforward, network_graph::NetworkGraph, Network},
+    };
+
+    fn sample_network() -> Feedforward {
+        let input_number = 2;
+        let output_number = 1;
+        let mut innov_record = InnovationRecord::new(input_number, output_number);
+        let mut network = Feedforward::new(
+            input_number,
+            output_number,
+            ActivationKind::Tanh,
+            &mut innov_record,
+        );
+        network.mutate_add_node(0.into(), ActivationKind::Sigmoid, &mut innov_record);
+        network.mutate_add_connection(1.into(), 4.into(), -0.5, &mut innov_record);
+        network.mutate_perturb_weight(1.into(), 0.25, -10.0, 10.0);
+        network.evaluate(3.5);
+
+        network
+    }
+
+    fn assert_same_network(a: &mut Feedforward, b: &mut Feedforward) {
+        assert_eq!(a.fitness(), b.fitness());
+        assert_eq!(a.graph().to_string(), b.graph().to_string());
+        assert_eq!(
+            a.activate(&[0.3, -1.2]).unwrap(),
+            b.activate(&[0.3, -1.2]).unwrap()
+        );
+    }
+
+    #[test]
+    fn network_should_roundtrip_through_json() {
+        let mut network = sample_network();
+        let serialized = to_string(&network, Format::Json).unwrap();
+        let mut deserialized: Feedforward = from_str(&serialized, Format::Json).unwrap();
+
+        assert_same_network(&mut network, &mut deserialized);
+    }
+
+    #[test]
+    fn network_should_roundtrip_through_toml() {
+        let mut network = sample_network();
+        let serialized = to_string(&network, Format::Toml).unwrap();
+        let mut deserialized: Feedforward = from_str(&serialized, Format::Toml).unwrap();
+
+        assert_same_network(&mut network, &mut deserialized);
+    }
+
+    #[test]
+    fn network_should_be_saved_and_loaded() {
+        let mut network = sample_network();
+        let path = std::env::temp_dir().join(format!("neat-save-test-{}.json", std::process::id()));
+
+        save(&network, &path).unwrap();
+        let mut loaded: Feedforward = load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_same_network(&mut network, &mut loaded);
+    }
+
+    #[test]
+    fn nodes_without_activation_should_load_as_sigmoid() {
+        let json = r#"{
+            "input_number": 1,
+            "output_number": 1,
+            "nodes": [
+                { "kind": "Input", "id": 0 },
+                { "kind": "Output", "id": 1 },
+                { "kind": "Bias", "id": 2 },
+                { "kind": "Hidden", "id": 3 }
+            ],
+            "edges": [
+                { "source": 0, "target": 3, "weight": 1.0, "disabled": false, "innov_number": 1 },
+                { "source": 3, "target": 1, "weight": 1.0, "disabled": false, "innov_number": 2 }
+            ]
+        }"#;
+        let graph: NetworkGraph = from_str(json, Format::Json).unwrap();
+
+        let nodes = graph.inner_data().raw_nodes();
+        assert_eq!(nodes[1].weight.activation(), ActivationKind::Sigmoid);
+        assert_eq!(nodes[3].weight.activation(), ActivationKind::Sigmoid);
+    }
+
+    #[test]
+    fn graph_with_unknown_node_should_be_rejected() {
+        let json = r#"{
+            "input_number": 1,
+            "output_number": 1,
+            "nodes": [
+                { "kind": "Input", "id": 0, "activation": "Linear" },
+                { "kind": "Output", "id": 1, "activation": "Sigmoid" },
+                { "kind": "Bias", "id": 2, "activation": "Linear" }
+            ],
+            "edges": [
+                { "source": 0, "target": 5, "weight": 1.0, "disabled": false, "innov_number": 0 }
+            ]
+        }"#;
+
+        assert!(from_str::<NetworkGraph>(json, Format::Json).is_err());
+    }
+}
@@ -1,15 +1,19 @@
-use rand::{distributions::Uniform, prelude::Distribution, Rng, RngCore};
+use rand::{Rng, RngCore};
 
-use crate::{activations::ActivationKind, network::Network};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::NeatError, network::Network, pareto};
 use std::fmt::Debug;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SpeciesInfo<T: Network + Debug + Clone> {
     id: usize,
     representative: T,
     age: usize,
 
-    previous_fitness: Option<f64>,
+    // Best raw fitness the species has reached, which stagnation is counted against
+    #[serde(default)]
+    best_fitness: Option<f64>,
     stagnant: usize,
 }
 
@@ -19,7 +23,7 @@ impl<T: Network + Debug + Clone> SpeciesInfo<T> {
             id,
             representative,
             age,
-            previous_fitness: None,
+            best_fitness: None,
             stagnant: 0,
         }
     }
@@ -33,6 +37,10 @@ impl<T: Network + Debug + Clone> SpeciesInfo<T> {
 pub struct Species<'a, T: Network + Debug + Clone> {
     info: SpeciesInfo<T>,
     list: Vec<&'a T>,
+
+    // Scores the genomes are selected by, in the order of the list. These are the fitness
+    // unless evaluation assigned other scores, e.g. novelty blended with fitness.
+    scores: Vec<f64>,
 }
 
 impl<'a, T: Network + Debug + Clone> Species<'a, T> {
@@ -40,6 +48,7 @@ impl<'a, T: Network + Debug + Clone> Species<'a, T> {
         Species {
             list: Vec::new(),
             info,
+            scores: Vec::new(),
         }
     }
 
@@ -47,23 +56,41 @@ impl<'a, T: Network + Debug + Clone> Species<'a, T> {
         self.info
     }
 
-    pub fn try_assign(&mut self, network: &'a T, c1: f64, c2: f64, threshold: f64) -> bool {
-        let metric = self
-            .info
-            .representative
-            .graph()
-            .compatibility_metric(network.graph(), c1, c2);
+    pub fn try_assign(
+        &mut self,
+        network: &'a T,
+        score: f64,
+        c1: f64,
+        c2: f64,
+        c3: f64,
+        threshold: f64,
+    ) -> bool {
+        let metric =
+            self.info
+                .representative
+                .graph()
+                .compatibility_metric(network.graph(), c1, c2, c3);
 
         if metric <= threshold {
-            self.list.push(network);
+            self.force_assign(network, score);
             true
         } else {
             false
         }
     }
 
-    pub fn force_assign(&mut self, network: &'a T) {
+    pub fn force_assign(&mut self, network: &'a T, score: f64) {
         self.list.push(network);
+        self.scores.push(score);
+    }
+
+    // Orders genomes by Pareto front and crowding distance of their objectives, so that
+    // survivors and elites are picked by NSGA-II instead of fitness
+    pub fn sort_by_pareto(&mut self) {
+        let objectives: Vec<&[f64]> = self.list.iter().map(|n| n.objectives()).collect();
+        let order = pareto::nsga2_order(&objectives);
+        self.list = order.iter().map(|&i| self.list[i]).collect();
+        self.scores = order.iter().map(|&i| self.scores[i]).collect();
     }
 
     pub fn kill_worst(&mut self, survival_rate: f64) {
@@ -72,19 +99,17 @@ impl<'a, T: Network + Debug + Clone> Species<'a, T> {
             remaining = 1;
         }
         self.list.truncate(remaining);
+        self.scores.truncate(remaining);
     }
 
-    pub fn replace_representative(&mut self, rng: &mut impl RngCore) {
-        let index = rng.gen_range(0..self.list.len());
-        self.info.representative = self.list[index].clone();
-    }
-
+    // Adjusted fitness is shared from the scores, while stagnation is tracked on fitness
     pub fn update_adjusted_fitness(&mut self) -> Option<f64> {
-        let mut sum = 0.0;
+        let sum: f64 = self.scores.iter().sum();
+        let mut best = f64::NEG_INFINITY;
         let len = self.list.len() as f64;
 
         for network in &self.list {
-            sum += network.fitness()?;
+            best = best.max(network.fitness()?);
         }
 
         let fitness = if self.list.is_empty() {
@@ -93,12 +118,13 @@ impl<'a, T: Network + Debug + Clone> Species<'a, T> {
             sum / len / len
         };
 
-        if fitness <= self.info.previous_fitness.unwrap_or(0.0) {
+        // Adjusted fitness drops as the species grows, so it can't tell stagnation
+        if best <= self.info.best_fitness.unwrap_or(0.0) {
             self.info.stagnant += 1;
         } else {
             self.info.stagnant = 0;
+            self.info.best_fitness = Some(best);
         }
-        self.info.previous_fitness = Some(fitness);
 
         Some(fitness)
     }
@@ -112,24 +138,24 @@ impl<'a, T: Network + Debug + Clone> Species<'a, T> {
         self.list[index].clone()
     }
 
-    pub fn mate(
-        &self,
-        rng: &mut impl RngCore,
-        hidden_func: ActivationKind,
-        output_func: ActivationKind,
-    ) -> Option<T> {
-        let uniform = Uniform::new(0, self.list.len());
+    pub fn mate(&self, rng: &mut impl RngCore) -> Result<T, NeatError> {
+        if self.list.len() < 2 {
+            return Err(NeatError::IncompatibleCrossover(
+                "species has less than two genomes".to_owned(),
+            ));
+        }
 
-        let index1 = uniform.sample(rng);
-        let mut index2 = index1;
-        while index1 == index2 {
-            index2 = uniform.sample(rng);
+        // Second parent is picked among the others, so a genome never mates with itself
+        let index1 = rng.gen_range(0..self.list.len());
+        let mut index2 = rng.gen_range(0..self.list.len() - 1);
+        if index2 >= index1 {
+            index2 += 1;
         }
 
         let parent1 = self.list[index1];
         let parent2 = self.list[index2];
 
-        parent1.crossover(parent2, hidden_func, output_func)
+        parent1.crossover(parent2, rng)
     }
 
     pub fn elites(&self, count: usize) -> Vec<T> {
@@ -0,0 +1,219 @@
+use std::io::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{network::Network, phase::SearchPhase, serialization::SerializationError};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SpeciesStats {
+    pub id: usize,
+    pub age: usize,
+    pub size: usize,
+    pub offspring: usize,
+    pub adjusted_fitness: f64,
+
+    // Number of generations without improvement of best fitness
+    pub stagnation: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GenerationStats {
+    pub generation: usize,
+
+    pub best_fitness: f64,
+    pub mean_fitness: f64,
+    pub std_fitness: f64,
+    pub median_fitness: f64,
+
+    pub best_node_count: usize,
+    pub best_edge_count: usize,
+    // Mean number of hidden nodes and enabled edges
+    #[serde(default)]
+    pub mean_complexity: f64,
+
+    // Phase the offspring of the generation are mutated in
+    #[serde(default)]
+    pub phase: SearchPhase,
+
+    // Threshold the generation is speciated with
+    pub compatibility_threshold: f64,
+
+    // Empty until the generation has been evolved
+    pub species: Vec<SpeciesStats>,
+}
+
+fn mean_std(list: &[f64]) -> (f64, f64) {
+    let mean = list.iter().sum::<f64>() / list.len() as f64;
+    let variance = list.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / list.len() as f64;
+
+    (mean, variance.sqrt())
+}
+
+// Assumes the list is sorted
+fn median(sorted: &[f64]) -> f64 {
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 1 {
+        sorted[mid]
+    } else {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    }
+}
+
+impl GenerationStats {
+    // Assumes genomes are evaluated and sorted by fitness in descending order
+    pub fn from_evaluation<T: Network>(
+        generation: usize,
+        compatibility_threshold: f64,
+        list: &[T],
+    ) -> Self {
+        let fitness_list: Vec<f64> = list.iter().map(|g| g.fitness().unwrap()).collect();
+        let (mean_fitness, std_fitness) = mean_std(&fitness_list);
+        let complexity_list: Vec<f64> = list.iter().map(|g| g.complexity() as f64).collect();
+
+        Self {
+            generation,
+            best_fitness: fitness_list[0],
+            mean_fitness,
+            std_fitness,
+            median_fitness: median(&fitness_list),
+            best_node_count: list[0].graph().node_count(),
+            best_edge_count: list[0].graph().edge_count(),
+            mean_complexity: mean_std(&complexity_list).0,
+            phase: SearchPhase::default(),
+            compatibility_threshold,
+            species: Vec::new(),
+        }
+    }
+}
+
+const CSV_HEADER: &str = "generation,best_fitness,mean_fitness,std_fitness,median_fitness,\
+                          best_node_count,best_edge_count,compatibility_threshold,species_count,\
+                          mean_complexity,phase";
+const SPECIES_CSV_HEADER: &str = "generation,id,age,size,offspring,adjusted_fitness,stagnation";
+
+// Writes one row per generation; per-species stats are written by write_species_csv
+pub fn write_csv(history: &[GenerationStats], mut writer: impl Write) -> std::io::Result<()> {
+    writeln!(writer, "{}", CSV_HEADER)?;
+    for stats in history {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{},{},{},{},{}",
+            stats.generation,
+            stats.best_fitness,
+            stats.mean_fitness,
+            stats.std_fitness,
+            stats.median_fitness,
+            stats.best_node_count,
+            stats.best_edge_count,
+            stats.compatibility_threshold,
+            stats.species.len(),
+            stats.mean_complexity,
+            stats.phase
+        )?;
+    }
+
+    Ok(())
+}
+
+// Writes one row per species of each generation
+pub fn write_species_csv(
+    history: &[GenerationStats],
+    mut writer: impl Write,
+) -> std::io::Result<()> {
+    writeln!(writer, "{}", SPECIES_CSV_HEADER)?;
+    for stats in history {
+        for species in &stats.species {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{}",
+                stats.generation,
+                species.id,
+                species.age,
+                species.size,
+                species.offspring,
+                species.adjusted_fitness,
+                species.stagnation
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+// Writes one JSON object per line
+pub fn write_json_lines(
+    history: &[GenerationStats],
+    mut writer: impl Write,
+) -> Result<(), SerializationError> {
+    for stats in history {
+        serde_json::to_writer(&mut writer, stats)?;
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_stats() -> GenerationStats {
+        GenerationStats {
+            generation: 3,
+            best_fitness: 3.5,
+            mean_fitness: 2.0,
+            std_fitness: 0.5,
+            median_fitness: 1.75,
+            best_node_count: 5,
+            best_edge_count: 4,
+            mean_complexity: 2.5,
+            phase: SearchPhase::Simplifying,
+            compatibility_threshold: 3.0,
+            species: vec![SpeciesStats {
+                id: 1,
+                age: 2,
+                size: 10,
+                offspring: 12,
+                adjusted_fitness: 0.25,
+                stagnation: 0,
+            }],
+        }
+    }
+
+    #[test]
+    fn median_should_average_middle_values_of_even_list() {
+        assert_eq!(median(&[4.0, 3.0, 1.0]), 3.0);
+        assert_eq!(median(&[4.0, 3.0, 2.0, 1.0]), 2.5);
+    }
+
+    #[test]
+    fn history_should_be_written_as_csv() {
+        let mut csv = Vec::new();
+        write_csv(&[sample_stats()], &mut csv).unwrap();
+        let mut species_csv = Vec::new();
+        write_species_csv(&[sample_stats()], &mut species_csv).unwrap();
+
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            format!("{}\n3,3.5,2,0.5,1.75,5,4,3,1,2.5,simplifying\n", CSV_HEADER)
+        );
+        assert_eq!(
+            String::from_utf8(species_csv).unwrap(),
+            format!("{}\n3,1,2,10,12,0.25,0\n", SPECIES_CSV_HEADER)
+        );
+    }
+
+    #[test]
+    fn history_should_roundtrip_through_json_lines() {
+        let history = vec![sample_stats(), sample_stats()];
+        let mut json = Vec::new();
+        write_json_lines(&history, &mut json).unwrap();
+
+        let deserialized: Vec<GenerationStats> = String::from_utf8(json)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(deserialized, history);
+    }
+}
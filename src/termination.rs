@@ -0,0 +1,197 @@
+use std::time::Duration;
+
+use crate::stats::GenerationStats;
+
+type Predicate = Box<dyn FnMut(&GenerationStats) -> bool>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StopReason {
+    FitnessThreshold,
+    MaxGenerations,
+    TimeLimit,
+
+    // Best fitness hasn't improved for the given number of generations
+    NoImprovement,
+    Custom,
+}
+
+// Criteria for Pool::run, which stops as soon as any of them is met
+//
+//     let stop = StopCondition::new()
+//         .fitness_threshold(3.9)
+//         .max_generations(200)
+//         .no_improvement(30);
+#[derive(Default)]
+pub struct StopCondition {
+    fitness_threshold: Option<f64>,
+    max_generations: Option<usize>,
+    time_limit: Option<Duration>,
+    no_improvement: Option<usize>,
+    custom: Option<Predicate>,
+
+    // Progress of the run being checked
+    generations: usize,
+    best_fitness: Option<f64>,
+    stale_generations: usize,
+}
+
+impl StopCondition {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Stops when the best fitness of a generation reaches the threshold
+    pub fn fitness_threshold(mut self, threshold: f64) -> Self {
+        self.fitness_threshold = Some(threshold);
+        self
+    }
+
+    pub fn max_generations(mut self, generations: usize) -> Self {
+        self.max_generations = Some(generations);
+        self
+    }
+
+    pub fn time_limit(mut self, limit: Duration) -> Self {
+        self.time_limit = Some(limit);
+        self
+    }
+
+    pub fn no_improvement(mut self, generations: usize) -> Self {
+        self.no_improvement = Some(generations);
+        self
+    }
+
+    // Stops when the predicate returns true for the stats of an evaluated generation
+    pub fn custom(mut self, predicate: impl FnMut(&GenerationStats) -> bool + 'static) -> Self {
+        self.custom = Some(Box::new(predicate));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fitness_threshold.is_none()
+            && self.max_generations.is_none()
+            && self.time_limit.is_none()
+            && self.no_improvement.is_none()
+            && self.custom.is_none()
+    }
+
+    // Records an evaluated generation and returns the first criterion met, in the order
+    // of StopReason
+    pub(crate) fn check(
+        &mut self,
+        stats: &GenerationStats,
+        elapsed: Duration,
+    ) -> Option<StopReason> {
+        self.generations += 1;
+        if self
+            .best_fitness
+            .is_none_or(|best| stats.best_fitness > best)
+        {
+            self.best_fitness = Some(stats.best_fitness);
+            self.stale_generations = 0;
+        } else {
+            self.stale_generations += 1;
+        }
+
+        if self
+            .fitness_threshold
+            .is_some_and(|threshold| stats.best_fitness >= threshold)
+        {
+            Some(StopReason::FitnessThreshold)
+        } else if self
+            .max_generations
+            .is_some_and(|max| self.generations >= max)
+        {
+            Some(StopReason::MaxGenerations)
+        } else if self.time_limit.is_some_and(|limit| elapsed >= limit) {
+            Some(StopReason::TimeLimit)
+        } else if self
+            .no_improvement
+            .is_some_and(|generations| self.stale_generations >= generations)
+        {
+            Some(StopReason::NoImprovement)
+        } else if self
+            .custom
+            .as_mut()
+            .is_some_and(|predicate| predicate(stats))
+        {
+            Some(StopReason::Custom)
+        } else {
+            None
+        }
+    }
+}
+
+// Result of Pool::run
+#[derive(Clone, Debug)]
+pub struct RunSummary<T> {
+    // Best genome evaluated during the run
+    pub champion: T,
+    pub reason: StopReason,
+
+    // Number of evaluated generations, including the last one which isn't evolved
+    pub generations: usize,
+    pub elapsed: Duration,
+
+    // Stats of every generation of the run, in order. The last entry has no species stats.
+    pub history: Vec<GenerationStats>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(best_fitness: f64) -> GenerationStats {
+        GenerationStats {
+            generation: 0,
+            best_fitness,
+            mean_fitness: 0.0,
+            std_fitness: 0.0,
+            median_fitness: 0.0,
+            best_node_count: 0,
+            best_edge_count: 0,
+            mean_complexity: 0.0,
+            phase: Default::default(),
+            compatibility_threshold: 0.0,
+            species: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn stop_condition_should_report_first_criterion_met() {
+        let mut stop = StopCondition::new()
+            .fitness_threshold(3.0)
+            .max_generations(3);
+        assert_eq!(stop.check(&stats(1.0), Duration::ZERO), None);
+        assert_eq!(stop.check(&stats(2.0), Duration::ZERO), None);
+        assert_eq!(
+            stop.check(&stats(3.0), Duration::ZERO),
+            Some(StopReason::FitnessThreshold)
+        );
+
+        let mut stop = StopCondition::new().time_limit(Duration::from_secs(1));
+        assert_eq!(stop.check(&stats(1.0), Duration::from_millis(10)), None);
+        assert_eq!(
+            stop.check(&stats(1.0), Duration::from_secs(1)),
+            Some(StopReason::TimeLimit)
+        );
+    }
+
+    #[test]
+    fn stop_condition_should_count_generations_without_improvement() {
+        let mut stop = StopCondition::new()
+            .no_improvement(2)
+            .custom(|stats| stats.best_fitness < 0.0);
+        assert_eq!(stop.check(&stats(1.0), Duration::ZERO), None);
+        assert_eq!(stop.check(&stats(1.0), Duration::ZERO), None);
+        assert_eq!(stop.check(&stats(1.5), Duration::ZERO), None);
+        assert_eq!(
+            stop.check(&stats(-1.0), Duration::ZERO),
+            Some(StopReason::Custom)
+        );
+        assert_eq!(
+            stop.check(&stats(1.5), Duration::ZERO),
+            Some(StopReason::NoImprovement)
+        );
+    }
+}